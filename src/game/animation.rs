@@ -1,12 +0,0 @@
-//! Player sprite animation.
-//! This is based on multiple examples and may be very different for your game.
-//! - [Sprite flipping](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_flipping.rs)
-//! - [Sprite animation](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_animation.rs)
-//! - [Timers](https://github.com/bevyengine/bevy/blob/latest/examples/time/timers.rs)
-
-use bevy::prelude::*;
-use bevy_spritesheet_animation::plugin::SpritesheetAnimationPlugin;
-
-pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(SpritesheetAnimationPlugin);
-}
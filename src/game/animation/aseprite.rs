@@ -0,0 +1,971 @@
+//! Loads [`AnimationSet`]s straight from `.aseprite` files.
+//! Every frame of the file becomes a cell of a horizontal sprite strip,
+//! and every frame tag becomes an animation named after the tag.
+//! Files without tags get a single animation over all frames, named after the file.
+//!
+//! Only what the game needs is supported: visible normal layers composited with
+//! normal blending, raw, linked and compressed cels, and all three color depths.
+//! See the [file format spec](https://github.com/aseprite/aseprite/blob/main/docs/ase-file-specs.md).
+
+use std::io::Read;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use flate2::read::ZlibDecoder;
+use thiserror::Error;
+
+use super::{AnimationSet, DurationDef, RepeatDef, SetAnimation};
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+/// Largest image a file may produce, in bytes. The frames of a file end up side by side
+/// in one image, which has to fit in memory and on the GPU.
+const MAX_IMAGE_BYTES: usize = 256 << 20;
+
+#[derive(Debug, Error)]
+pub enum AsepriteError {
+    #[error("could not read aseprite file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not an aseprite file")]
+    BadMagic,
+    #[error("aseprite file ends unexpectedly")]
+    UnexpectedEof,
+    #[error("unsupported color depth {0}")]
+    UnsupportedDepth(u16),
+    #[error("aseprite file has no frames")]
+    NoFrames,
+    #[error("aseprite image of {0}x{1} pixels is too large")]
+    TooLarge(usize, usize),
+}
+
+#[derive(Default)]
+pub(super) struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = AnimationSet;
+    type Settings = ();
+    type Error = AsepriteError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let sprite = AsepriteFile::parse(&bytes)?;
+
+        // Both fit in a `u16`, so these can't overflow.
+        let frame_count = sprite.frames.len() as u32;
+        let image = load_context.add_labeled_asset("image".to_string(), sprite.strip_image());
+        let layout = load_context.add_labeled_asset(
+            "layout".to_string(),
+            TextureAtlasLayout::from_grid(
+                UVec2::new(sprite.width as u32, sprite.height as u32),
+                frame_count,
+                1,
+                None,
+                None,
+            ),
+        );
+
+        let mut animations = sprite
+            .tags
+            .iter()
+            .map(|tag| sprite.animation(tag))
+            .collect::<Vec<_>>();
+        if animations.is_empty() {
+            let name = load_context
+                .path()
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            animations.push(sprite.animation(&Tag {
+                name,
+                from: 0,
+                to: frame_count.saturating_sub(1) as usize,
+                direction: Direction::Forward,
+                repeat: 0,
+            }));
+        }
+
+        Ok(AnimationSet {
+            layout,
+            texture: Some(image),
+            animations,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite", "ase"]
+    }
+}
+
+/// The parts of an aseprite file needed to build an [`AnimationSet`].
+struct AsepriteFile {
+    width: usize,
+    height: usize,
+    /// Composited RGBA pixels of every frame.
+    frames: Vec<Frame>,
+    tags: Vec<Tag>,
+}
+
+struct Frame {
+    pixels: Vec<u8>,
+    duration_ms: u32,
+}
+
+struct Layer {
+    visible: bool,
+    /// Only normal image layers are drawn, groups and tilemaps are skipped.
+    drawable: bool,
+    opacity: u8,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+struct Tag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: Direction,
+    /// How often the tag plays, `0` meaning forever.
+    repeat: u16,
+}
+
+/// Little-endian cursor over the file bytes.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], AsepriteError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .ok_or(AsepriteError::UnexpectedEof)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(AsepriteError::UnexpectedEof)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), AsepriteError> {
+        self.take(n).map(|_| ())
+    }
+
+    fn byte(&mut self) -> Result<u8, AsepriteError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, AsepriteError> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn short(&mut self) -> Result<i16, AsepriteError> {
+        Ok(self.word()? as i16)
+    }
+
+    fn dword(&mut self) -> Result<u32, AsepriteError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, AsepriteError> {
+        let len = self.word()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+}
+
+impl AsepriteFile {
+    fn parse(bytes: &[u8]) -> Result<Self, AsepriteError> {
+        let mut header = Cursor::new(bytes);
+        header.skip(4)?;
+        if header.word()? != HEADER_MAGIC {
+            return Err(AsepriteError::BadMagic);
+        }
+        let frame_count = header.word()? as usize;
+        let width = header.word()? as usize;
+        let height = header.word()? as usize;
+        let depth = header.word()?;
+        if !matches!(depth, 8 | 16 | 32) {
+            return Err(AsepriteError::UnsupportedDepth(depth));
+        }
+        if frame_count == 0 {
+            return Err(AsepriteError::NoFrames);
+        }
+        // All frames end up side by side in one image.
+        image_len(width.saturating_mul(frame_count), height)?;
+        header.skip(14)?;
+        let transparent_index = header.byte()?;
+
+        let mut layers = Vec::<Layer>::new();
+        // Visibility of the enclosing groups, indexed by child level.
+        let mut group_visible = Vec::<bool>::new();
+        let mut palette = vec![[0u8; 4]; 256];
+        let mut tags = vec![];
+        let mut frames = Vec::<Frame>::with_capacity(frame_count);
+        // Decoded cels of every frame, kept around because linked cels refer to earlier frames.
+        let mut cels = Vec::<Vec<Cel>>::with_capacity(frame_count);
+
+        let mut cursor = Cursor::new(bytes);
+        cursor.skip(128)?;
+        for _ in 0..frame_count {
+            let frame_start = cursor.pos;
+            let frame_size = cursor.dword()? as usize;
+            if cursor.word()? != FRAME_MAGIC {
+                return Err(AsepriteError::BadMagic);
+            }
+            let old_chunks = cursor.word()? as u32;
+            let duration_ms = cursor.word()? as u32;
+            cursor.skip(2)?;
+            let new_chunks = cursor.dword()?;
+            let chunk_count = if new_chunks == 0 {
+                old_chunks
+            } else {
+                new_chunks
+            };
+
+            let mut frame_cels = vec![];
+            for _ in 0..chunk_count {
+                let chunk_start = cursor.pos;
+                let chunk_size = cursor.dword()? as usize;
+                let chunk_type = cursor.word()?;
+                let mut chunk = Cursor::new(cursor.take(chunk_size.saturating_sub(6))?);
+                match chunk_type {
+                    CHUNK_LAYER => layers.push(parse_layer(&mut chunk, &mut group_visible)?),
+                    CHUNK_CEL => {
+                        if let Some(cel) =
+                            parse_cel(&mut chunk, depth, &palette, transparent_index, &cels)?
+                        {
+                            frame_cels.push(cel);
+                        }
+                    }
+                    CHUNK_TAGS => tags = parse_tags(&mut chunk)?,
+                    CHUNK_PALETTE => parse_palette(&mut chunk, &mut palette)?,
+                    _ => {}
+                }
+                cursor.pos = chunk_start + chunk_size;
+            }
+            cursor.pos = frame_start + frame_size;
+
+            let pixels = composite(width, height, &layers, &frame_cels);
+            frames.push(Frame {
+                pixels,
+                duration_ms,
+            });
+            cels.push(frame_cels);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            frames,
+            tags,
+        })
+    }
+
+    /// All frames side by side in one image.
+    fn strip_image(&self) -> Image {
+        let strip_width = self.width * self.frames.len();
+        let row_bytes = self.width * 4;
+        let mut data = vec![0; strip_width * self.height * 4];
+        for (i, frame) in self.frames.iter().enumerate() {
+            for y in 0..self.height {
+                let src = &frame.pixels[y * row_bytes..(y + 1) * row_bytes];
+                let dst_start = (y * strip_width + i * self.width) * 4;
+                data[dst_start..dst_start + row_bytes].copy_from_slice(src);
+            }
+        }
+        let mut image = Image::new(
+            Extent3d {
+                width: strip_width as u32,
+                height: self.height as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.sampler = ImageSampler::nearest();
+        image
+    }
+
+    /// Turn a tag into an animation. Frames are repeated so that a single
+    /// per-frame duration reproduces the per-frame durations from the file.
+    fn animation(&self, tag: &Tag) -> SetAnimation {
+        let last = self.frames.len().saturating_sub(1);
+        let (from, to) = (tag.from.min(last), tag.to.min(last));
+        let forward = (from..=to).collect::<Vec<_>>();
+        let order = match tag.direction {
+            Direction::Forward => forward,
+            Direction::Reverse => forward.into_iter().rev().collect(),
+            Direction::PingPong => ping_pong(forward),
+            Direction::PingPongReverse => ping_pong(forward.into_iter().rev().collect()),
+        };
+
+        let step = order
+            .iter()
+            .map(|&i| self.frames[i].duration_ms.max(1))
+            .reduce(gcd)
+            .unwrap_or(100);
+        let indices = order
+            .iter()
+            .flat_map(|&i| {
+                let repeats = (self.frames[i].duration_ms.max(1) / step) as usize;
+                std::iter::repeat(i).take(repeats)
+            })
+            .collect();
+
+        SetAnimation {
+            name: tag.name.clone(),
+            stages: vec![indices],
+            repeat: match tag.repeat {
+                0 => RepeatDef::Loop,
+                n => RepeatDef::Cycles(n as u32),
+            },
+            duration: Some(DurationDef::PerFrame(step)),
+        }
+    }
+}
+
+/// `[0, 1, 2]` becomes `[0, 1, 2, 1]`, so looping doesn't show the ends twice.
+fn ping_pong(mut order: Vec<usize>) -> Vec<usize> {
+    let back = order
+        .iter()
+        .rev()
+        .skip(1)
+        .take(order.len().saturating_sub(2))
+        .copied()
+        .collect::<Vec<_>>();
+    order.extend(back);
+    order
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn parse_layer(chunk: &mut Cursor, group_visible: &mut Vec<bool>) -> Result<Layer, AsepriteError> {
+    let flags = chunk.word()?;
+    let layer_type = chunk.word()?;
+    let child_level = chunk.word()? as usize;
+    // Default size and blend mode, which are unused.
+    chunk.skip(6)?;
+    let opacity = chunk.byte()?;
+
+    // A layer is only shown if all groups it is nested in are shown as well.
+    group_visible.truncate(child_level);
+    let visible = flags & 1 != 0 && group_visible.last().copied().unwrap_or(true);
+    group_visible.push(visible);
+    Ok(Layer {
+        visible,
+        drawable: layer_type == 0,
+        opacity,
+    })
+}
+
+/// A decoded cel, with its pixels converted to RGBA.
+#[derive(Clone)]
+struct Cel {
+    layer: usize,
+    x: i32,
+    y: i32,
+    opacity: u8,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+fn parse_cel(
+    chunk: &mut Cursor,
+    depth: u16,
+    palette: &[[u8; 4]],
+    transparent_index: u8,
+    previous: &[Vec<Cel>],
+) -> Result<Option<Cel>, AsepriteError> {
+    let layer = chunk.word()? as usize;
+    let x = chunk.short()? as i32;
+    let y = chunk.short()? as i32;
+    let opacity = chunk.byte()?;
+    let cel_type = chunk.word()?;
+    chunk.skip(7)?;
+
+    match cel_type {
+        // Raw and compressed image data.
+        0 | 2 => {
+            let width = chunk.word()? as usize;
+            let height = chunk.word()? as usize;
+            let len = image_len(width, height)?;
+            let rest = &chunk.bytes[chunk.pos..];
+            let raw = if cel_type == 2 {
+                let mut raw = vec![];
+                ZlibDecoder::new(rest).read_to_end(&mut raw)?;
+                raw
+            } else {
+                rest.to_vec()
+            };
+            let pixels = to_rgba(&raw, depth, palette, transparent_index);
+            if pixels.len() < len {
+                return Err(AsepriteError::UnexpectedEof);
+            }
+            Ok(Some(Cel {
+                layer,
+                x,
+                y,
+                opacity,
+                width,
+                height,
+                pixels,
+            }))
+        }
+        // Linked cel, reusing the cel of the same layer in an earlier frame.
+        1 => {
+            let frame = chunk.word()? as usize;
+            Ok(previous.get(frame).and_then(|cels| {
+                cels.iter().find(|cel| cel.layer == layer).map(|cel| Cel {
+                    x,
+                    y,
+                    opacity,
+                    ..cel.clone()
+                })
+            }))
+        }
+        // Tilemaps are not supported.
+        _ => Ok(None),
+    }
+}
+
+fn to_rgba(raw: &[u8], depth: u16, palette: &[[u8; 4]], transparent_index: u8) -> Vec<u8> {
+    match depth {
+        32 => raw.to_vec(),
+        16 => raw
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        _ => raw
+            .iter()
+            .flat_map(|&i| {
+                if i == transparent_index {
+                    [0; 4]
+                } else {
+                    palette[i as usize]
+                }
+            })
+            .collect(),
+    }
+}
+
+fn parse_tags(chunk: &mut Cursor) -> Result<Vec<Tag>, AsepriteError> {
+    let count = chunk.word()?;
+    chunk.skip(8)?;
+    (0..count)
+        .map(|_| {
+            let from = chunk.word()? as usize;
+            let to = chunk.word()? as usize;
+            let direction = match chunk.byte()? {
+                1 => Direction::Reverse,
+                2 => Direction::PingPong,
+                3 => Direction::PingPongReverse,
+                _ => Direction::Forward,
+            };
+            let repeat = chunk.word()?;
+            chunk.skip(10)?;
+            let name = chunk.string()?;
+            Ok(Tag {
+                name,
+                from,
+                to,
+                direction,
+                repeat,
+            })
+        })
+        .collect()
+}
+
+fn parse_palette(chunk: &mut Cursor, palette: &mut Vec<[u8; 4]>) -> Result<(), AsepriteError> {
+    let size = chunk.dword()? as usize;
+    let first = chunk.dword()? as usize;
+    let last = chunk.dword()? as usize;
+    chunk.skip(8)?;
+    if palette.len() < size {
+        palette.resize(size, [0; 4]);
+    }
+    for index in first..=last {
+        let flags = chunk.word()?;
+        let color = [chunk.byte()?, chunk.byte()?, chunk.byte()?, chunk.byte()?];
+        if flags & 1 != 0 {
+            chunk.string()?;
+        }
+        if let Some(entry) = palette.get_mut(index) {
+            *entry = color;
+        }
+    }
+    Ok(())
+}
+
+/// Number of bytes in an RGBA image of the given size, unless it's larger than any
+/// sprite sheet should be.
+fn image_len(width: usize, height: usize) -> Result<usize, AsepriteError> {
+    width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .filter(|&len| len <= MAX_IMAGE_BYTES)
+        .ok_or(AsepriteError::TooLarge(width, height))
+}
+
+/// Draw the cels of one frame on top of each other in layer order.
+fn composite(width: usize, height: usize, layers: &[Layer], cels: &[Cel]) -> Vec<u8> {
+    let mut canvas = vec![0u8; width * height * 4];
+    let mut ordered = cels.iter().collect::<Vec<_>>();
+    ordered.sort_by_key(|cel| cel.layer);
+    for cel in ordered {
+        let Some(layer) = layers.get(cel.layer) else {
+            continue;
+        };
+        if !layer.visible || !layer.drawable {
+            continue;
+        }
+        let opacity = layer.opacity as u32 * cel.opacity as u32 / 255;
+        for cy in 0..cel.height {
+            let Some(y) = canvas_coord(cel.y, cy, height) else {
+                continue;
+            };
+            for cx in 0..cel.width {
+                let Some(x) = canvas_coord(cel.x, cx, width) else {
+                    continue;
+                };
+                let src_i = (cy * cel.width + cx) * 4;
+                let dst_i = (y * width + x) * 4;
+                let src = &cel.pixels[src_i..src_i + 4];
+                blend_normal(&mut canvas[dst_i..dst_i + 4], src, opacity);
+            }
+        }
+    }
+    canvas
+}
+
+/// Where the pixel at `offset` in a cel placed at `origin` lands on the canvas, if it does.
+fn canvas_coord(origin: i32, offset: usize, size: usize) -> Option<usize> {
+    let coord = usize::try_from(i64::from(origin) + offset as i64).ok()?;
+    (coord < size).then_some(coord)
+}
+
+/// Straight-alpha "source over" blending.
+fn blend_normal(dst: &mut [u8], src: &[u8], opacity: u32) {
+    let src_a = src[3] as u32 * opacity / 255;
+    if src_a == 0 {
+        return;
+    }
+    let dst_a = dst[3] as u32;
+    let out_a = src_a + dst_a * (255 - src_a) / 255;
+    for c in 0..3 {
+        let blended = (src[c] as u32 * src_a + dst[c] as u32 * dst_a * (255 - src_a) / 255) / out_a;
+        dst[c] = blended as u8;
+    }
+    dst[3] = out_a as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::ZlibEncoder, Compression};
+
+    use super::*;
+
+    /// Builds aseprite files with 32-bit color, one chunk list per frame.
+    struct Fixture {
+        width: u16,
+        height: u16,
+        depth: u16,
+        frames: Vec<(u16, Vec<Vec<u8>>)>,
+    }
+
+    impl Fixture {
+        fn new(width: u16, height: u16) -> Self {
+            Self {
+                width,
+                height,
+                depth: 32,
+                frames: vec![],
+            }
+        }
+
+        fn frame(mut self, duration_ms: u16, chunks: Vec<Vec<u8>>) -> Self {
+            self.frames.push((duration_ms, chunks));
+            self
+        }
+
+        fn build(&self) -> Vec<u8> {
+            let mut body = vec![];
+            for (duration_ms, chunks) in &self.frames {
+                let data = chunks.concat();
+                body.extend(((data.len() + 16) as u32).to_le_bytes());
+                body.extend(FRAME_MAGIC.to_le_bytes());
+                body.extend((chunks.len() as u16).to_le_bytes());
+                body.extend(duration_ms.to_le_bytes());
+                body.extend([0; 2]);
+                body.extend((chunks.len() as u32).to_le_bytes());
+                body.extend(data);
+            }
+            let mut header = vec![];
+            header.extend(((body.len() + 128) as u32).to_le_bytes());
+            header.extend(HEADER_MAGIC.to_le_bytes());
+            header.extend((self.frames.len() as u16).to_le_bytes());
+            header.extend(self.width.to_le_bytes());
+            header.extend(self.height.to_le_bytes());
+            header.extend(self.depth.to_le_bytes());
+            header.resize(128, 0);
+            header.extend(body);
+            header
+        }
+    }
+
+    fn chunk(chunk_type: u16, data: &[u8]) -> Vec<u8> {
+        let mut chunk = ((data.len() + 6) as u32).to_le_bytes().to_vec();
+        chunk.extend(chunk_type.to_le_bytes());
+        chunk.extend(data);
+        chunk
+    }
+
+    fn string(s: &str) -> Vec<u8> {
+        let mut bytes = (s.len() as u16).to_le_bytes().to_vec();
+        bytes.extend(s.as_bytes());
+        bytes
+    }
+
+    fn layer(flags: u16, layer_type: u16, child_level: u16) -> Vec<u8> {
+        let mut data = vec![];
+        for word in [flags, layer_type, child_level, 0, 0, 0] {
+            data.extend(word.to_le_bytes());
+        }
+        data.extend([255, 0, 0, 0]);
+        data.extend(string("Layer"));
+        chunk(CHUNK_LAYER, &data)
+    }
+
+    fn cel_header(layer: u16, x: i16, y: i16, cel_type: u16) -> Vec<u8> {
+        let mut data = layer.to_le_bytes().to_vec();
+        data.extend(x.to_le_bytes());
+        data.extend(y.to_le_bytes());
+        data.push(255);
+        data.extend(cel_type.to_le_bytes());
+        data.extend([0; 7]);
+        data
+    }
+
+    fn image_cel(
+        layer: u16,
+        x: i16,
+        y: i16,
+        size: (u16, u16),
+        pixels: &[u8],
+        zlib: bool,
+    ) -> Vec<u8> {
+        let mut data = cel_header(layer, x, y, if zlib { 2 } else { 0 });
+        data.extend(size.0.to_le_bytes());
+        data.extend(size.1.to_le_bytes());
+        if zlib {
+            let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+            encoder.write_all(pixels).unwrap();
+            data.extend(encoder.finish().unwrap());
+        } else {
+            data.extend(pixels);
+        }
+        chunk(CHUNK_CEL, &data)
+    }
+
+    fn linked_cel(layer: u16, frame: u16) -> Vec<u8> {
+        let mut data = cel_header(layer, 0, 0, 1);
+        data.extend(frame.to_le_bytes());
+        chunk(CHUNK_CEL, &data)
+    }
+
+    fn tags(tags: &[(&str, u16, u16, u8, u16)]) -> Vec<u8> {
+        let mut data = (tags.len() as u16).to_le_bytes().to_vec();
+        data.extend([0; 8]);
+        for (name, from, to, direction, repeat) in tags {
+            data.extend(from.to_le_bytes());
+            data.extend(to.to_le_bytes());
+            data.push(*direction);
+            data.extend(repeat.to_le_bytes());
+            data.extend([0; 10]);
+            data.extend(string(name));
+        }
+        chunk(CHUNK_TAGS, &data)
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    fn pixel(file: &AsepriteFile, frame: usize, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * file.width + x) * 4;
+        file.frames[frame].pixels[i..i + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn parses_header_and_frames() {
+        let bytes = Fixture::new(3, 2)
+            .frame(100, vec![layer(1, 0, 0)])
+            .frame(250, vec![])
+            .build();
+        let file = AsepriteFile::parse(&bytes).unwrap();
+        assert_eq!((file.width, file.height), (3, 2));
+        assert_eq!(file.frames.len(), 2);
+        assert_eq!(file.frames[0].duration_ms, 100);
+        assert_eq!(file.frames[1].duration_ms, 250);
+        assert_eq!(file.frames[1].pixels, vec![0; 3 * 2 * 4]);
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let mut bytes = Fixture::new(1, 1).frame(100, vec![]).build();
+        bytes[4] = 0;
+        assert!(matches!(
+            AsepriteFile::parse(&bytes),
+            Err(AsepriteError::BadMagic)
+        ));
+
+        let bytes = Fixture::new(1, 1).build();
+        assert!(matches!(
+            AsepriteFile::parse(&bytes),
+            Err(AsepriteError::NoFrames)
+        ));
+
+        let mut fixture = Fixture::new(1, 1).frame(100, vec![]);
+        fixture.depth = 24;
+        assert!(matches!(
+            AsepriteFile::parse(&fixture.build()),
+            Err(AsepriteError::UnsupportedDepth(24))
+        ));
+
+        let bytes = Fixture::new(1, 1).frame(100, vec![layer(1, 0, 0)]).build();
+        assert!(matches!(
+            AsepriteFile::parse(&bytes[..bytes.len() - 4]),
+            Err(AsepriteError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn skips_unknown_chunks() {
+        let bytes = Fixture::new(1, 1)
+            .frame(
+                100,
+                vec![
+                    layer(1, 0, 0),
+                    chunk(0x2007, &[1, 2, 3]),
+                    image_cel(0, 0, 0, (1, 1), &RED, false),
+                ],
+            )
+            .build();
+        let file = AsepriteFile::parse(&bytes).unwrap();
+        assert_eq!(pixel(&file, 0, 0, 0), RED);
+    }
+
+    #[test]
+    fn decodes_raw_zlib_and_linked_cels() {
+        let pixels = [RED, BLUE].concat();
+        let bytes = Fixture::new(3, 2)
+            .frame(
+                100,
+                vec![layer(1, 0, 0), image_cel(0, 1, 1, (2, 1), &pixels, true)],
+            )
+            .frame(100, vec![linked_cel(0, 0)])
+            .frame(100, vec![image_cel(0, 0, 0, (2, 1), &pixels, false)])
+            .build();
+        let file = AsepriteFile::parse(&bytes).unwrap();
+        assert_eq!(pixel(&file, 0, 0, 0), [0; 4]);
+        assert_eq!(pixel(&file, 0, 1, 1), RED);
+        assert_eq!(pixel(&file, 0, 2, 1), BLUE);
+        // Linked cels take their position from the link, not from the cel they link to.
+        assert_eq!(pixel(&file, 1, 0, 0), RED);
+        assert_eq!(pixel(&file, 1, 1, 0), BLUE);
+        assert_eq!(pixel(&file, 2, 1, 0), BLUE);
+    }
+
+    #[test]
+    fn composites_visible_layers_in_order() {
+        let bytes = Fixture::new(2, 1)
+            .frame(
+                100,
+                vec![
+                    layer(1, 0, 0),
+                    layer(1, 0, 0),
+                    // Hidden, so it doesn't cover the others.
+                    layer(0, 0, 0),
+                    image_cel(1, 0, 0, (1, 1), &BLUE, false),
+                    image_cel(0, 0, 0, (2, 1), &[RED, RED].concat(), false),
+                    image_cel(2, 0, 0, (2, 1), &[BLUE, BLUE].concat(), false),
+                ],
+            )
+            .build();
+        let file = AsepriteFile::parse(&bytes).unwrap();
+        assert_eq!(pixel(&file, 0, 0, 0), BLUE);
+        assert_eq!(pixel(&file, 0, 1, 0), RED);
+    }
+
+    #[test]
+    fn parses_tags() {
+        let bytes = Fixture::new(1, 1)
+            .frame(
+                100,
+                vec![
+                    layer(1, 0, 0),
+                    tags(&[("walk", 0, 1, 0, 0), ("spin", 1, 1, 2, 3)]),
+                ],
+            )
+            .frame(100, vec![])
+            .build();
+        let file = AsepriteFile::parse(&bytes).unwrap();
+        assert_eq!(file.tags.len(), 2);
+        assert_eq!(file.tags[0].name, "walk");
+        assert_eq!((file.tags[0].from, file.tags[0].to), (0, 1));
+        assert!(matches!(file.tags[0].direction, Direction::Forward));
+        assert_eq!(file.tags[1].name, "spin");
+        assert!(matches!(file.tags[1].direction, Direction::PingPong));
+        assert_eq!(file.tags[1].repeat, 3);
+    }
+
+    #[test]
+    fn ping_pong_skips_the_ends_on_the_way_back() {
+        assert_eq!(ping_pong(vec![0, 1, 2]), vec![0, 1, 2, 1]);
+        assert_eq!(ping_pong(vec![3, 2, 1, 0]), vec![3, 2, 1, 0, 1, 2]);
+        assert_eq!(ping_pong(vec![0, 1]), vec![0, 1]);
+        assert_eq!(ping_pong(vec![5]), vec![5]);
+        assert_eq!(ping_pong(vec![]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn tags_become_animations() {
+        let bytes = Fixture::new(1, 1)
+            .frame(100, vec![layer(1, 0, 0)])
+            .frame(200, vec![])
+            .frame(100, vec![])
+            .build();
+        let file = AsepriteFile::parse(&bytes).unwrap();
+        let tag = |direction, repeat| Tag {
+            name: "tag".to_string(),
+            from: 0,
+            to: 2,
+            direction,
+            repeat,
+        };
+
+        // Longer frames are repeated so one duration per frame fits them all.
+        let forward = file.animation(&tag(Direction::Forward, 0));
+        assert_eq!(forward.name, "tag");
+        assert_eq!(forward.stages, vec![vec![0, 1, 1, 2]]);
+        assert!(matches!(forward.repeat, RepeatDef::Loop));
+        assert!(matches!(forward.duration, Some(DurationDef::PerFrame(100))));
+
+        let reverse = file.animation(&tag(Direction::Reverse, 2));
+        assert_eq!(reverse.stages, vec![vec![2, 1, 1, 0]]);
+        assert!(matches!(reverse.repeat, RepeatDef::Cycles(2)));
+
+        let ping_pong = file.animation(&tag(Direction::PingPong, 0));
+        assert_eq!(ping_pong.stages, vec![vec![0, 1, 1, 2, 1, 1]]);
+
+        let ping_pong_reverse = file.animation(&tag(Direction::PingPongReverse, 0));
+        assert_eq!(ping_pong_reverse.stages, vec![vec![2, 1, 1, 0, 1, 1]]);
+
+        // Tags reaching past the last frame are cut short.
+        let mut long = tag(Direction::Forward, 0);
+        long.to = 9;
+        assert_eq!(file.animation(&long).stages, vec![vec![0, 1, 1, 2]]);
+    }
+
+    #[test]
+    fn rejects_images_that_are_too_large() {
+        let bytes = Fixture::new(u16::MAX, u16::MAX).frame(100, vec![]).build();
+        assert!(matches!(
+            AsepriteFile::parse(&bytes),
+            Err(AsepriteError::TooLarge(..))
+        ));
+
+        // Each frame is small, but not all of them side by side.
+        let mut fixture = Fixture::new(u16::MAX, 1024);
+        for _ in 0..2 {
+            fixture = fixture.frame(100, vec![]);
+        }
+        assert!(matches!(
+            AsepriteFile::parse(&fixture.build()),
+            Err(AsepriteError::TooLarge(..))
+        ));
+
+        let bytes = Fixture::new(1, 1)
+            .frame(
+                100,
+                vec![
+                    layer(1, 0, 0),
+                    image_cel(0, 0, 0, (u16::MAX, u16::MAX), &RED, false),
+                ],
+            )
+            .build();
+        assert!(matches!(
+            AsepriteFile::parse(&bytes),
+            Err(AsepriteError::TooLarge(65535, 65535))
+        ));
+    }
+
+    #[test]
+    fn draws_cels_hanging_off_the_canvas() {
+        let pixels = [RED, BLUE, RED, BLUE].concat();
+        let bytes = Fixture::new(2, 2)
+            .frame(
+                100,
+                vec![layer(1, 0, 0), image_cel(0, -1, 1, (2, 2), &pixels, false)],
+            )
+            .build();
+        let file = AsepriteFile::parse(&bytes).unwrap();
+        assert_eq!(pixel(&file, 0, 0, 0), [0; 4]);
+        assert_eq!(pixel(&file, 0, 0, 1), BLUE);
+        assert_eq!(pixel(&file, 0, 1, 1), [0; 4]);
+    }
+
+    /// The sheet the player's sprites were drawn in. Every frame is a revision of the
+    /// whole sheet, so it has no tags and isn't cut into animations.
+    #[test]
+    fn parses_the_player_sheet() {
+        let bytes = include_bytes!("../../../assets/images/playerSheet.aseprite");
+        let file = AsepriteFile::parse(bytes).unwrap();
+        assert_eq!((file.width, file.height), (95, 210));
+        assert_eq!(file.frames.len(), 6);
+        assert!(file.tags.is_empty());
+        // The outline of the first cell of the 19x21 grid in the latest revision.
+        let last = file.frames.len() - 1;
+        assert_eq!(pixel(&file, last, 9, 10), [26, 26, 26, 255]);
+        assert_eq!(pixel(&file, last, 0, 0), [0; 4]);
+    }
+}
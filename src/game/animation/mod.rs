@@ -0,0 +1,367 @@
+//! Sprite animation sets, loaded from `*.anim.ron` or `*.aseprite` files.
+//! Every set describes the atlas layout of one sprite sheet and its named animations,
+//! which are registered in the [`SpritesheetLibrary`] when the set loads.
+//! Spawners look animations up by name through [`AnimationNames`]
+//! and share the set's atlas layout. Characters switch between them with a [`state`] machine.
+
+mod aseprite;
+pub mod state;
+
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_spritesheet_animation::{
+    animation::{AnimationDuration, AnimationId, AnimationRepeat},
+    component::SpritesheetAnimation,
+    library::SpritesheetLibrary,
+    plugin::SpritesheetAnimationPlugin,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(SpritesheetAnimationPlugin);
+    app.init_asset::<AnimationSet>();
+    app.init_asset_loader::<AnimationSetLoader>();
+    app.init_asset_loader::<aseprite::AsepriteLoader>();
+    app.init_resource::<AnimationNames>();
+    app.add_systems(Update, register_animations);
+    app.add_plugins(state::plugin);
+}
+
+/// A sprite sheet's atlas layout together with the animations it contains.
+#[derive(Asset, TypePath, Debug)]
+pub struct AnimationSet {
+    pub layout: Handle<TextureAtlasLayout>,
+    /// The sprite sheet itself, for sets that carry their own image.
+    pub texture: Option<Handle<Image>>,
+    animations: Vec<SetAnimation>,
+}
+
+/// An animation of an [`AnimationSet`] with its stages resolved to atlas indices.
+#[derive(Debug)]
+struct SetAnimation {
+    name: String,
+    stages: Vec<Vec<usize>>,
+    repeat: RepeatDef,
+    duration: Option<DurationDef>,
+}
+
+/// Ids of every registered animation by name.
+/// Entries are replaced when an [`AnimationSet`] is hot-reloaded.
+#[derive(Resource, Debug, Default)]
+pub struct AnimationNames(HashMap<String, AnimationId>);
+
+impl AnimationNames {
+    pub fn get(&self, name: &str) -> Option<AnimationId> {
+        self.0.get(name).copied()
+    }
+}
+
+/// Grid of equally sized frames in a sprite sheet. Sizes are in pixels.
+#[derive(Debug, Deserialize)]
+struct AtlasGrid {
+    tile_size: (u32, u32),
+    columns: u32,
+    rows: u32,
+    #[serde(default)]
+    padding: Option<(u32, u32)>,
+    #[serde(default)]
+    offset: Option<(u32, u32)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnimationSetDef {
+    atlas: AtlasGrid,
+    animations: Vec<AnimationDef>,
+}
+
+impl AnimationSetDef {
+    /// Parses a set and checks that every animation stays within its atlas.
+    fn parse(bytes: &[u8]) -> Result<Self, AnimationSetError> {
+        let set: Self = ron::de::from_bytes(bytes)?;
+        let atlas = &set.atlas;
+        if let Some(anim) = set.animations.iter().find(|anim| {
+            anim.stages.iter().any(|frames| {
+                frames.row >= atlas.rows
+                    || frames.last >= atlas.columns
+                    || frames.first > frames.last
+            })
+        }) {
+            return Err(AnimationSetError::OutOfBounds {
+                name: anim.name.clone(),
+                columns: atlas.columns,
+                rows: atlas.rows,
+            });
+        }
+        Ok(set)
+    }
+}
+
+/// A named animation made of one or more stages that play in order.
+#[derive(Debug, Deserialize)]
+struct AnimationDef {
+    name: String,
+    stages: Vec<FramesDef>,
+    #[serde(default)]
+    repeat: RepeatDef,
+    #[serde(default)]
+    duration: Option<DurationDef>,
+}
+
+/// A run of frames on one row of the atlas, `first` and `last` included.
+#[derive(Debug, Deserialize)]
+struct FramesDef {
+    row: u32,
+    first: u32,
+    last: u32,
+}
+
+impl FramesDef {
+    fn indices(&self, columns: u32) -> Vec<usize> {
+        (self.first..=self.last)
+            .map(|column| (self.row * columns + column) as usize)
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+enum RepeatDef {
+    #[default]
+    Loop,
+    Cycles(u32),
+}
+
+impl From<RepeatDef> for AnimationRepeat {
+    fn from(value: RepeatDef) -> Self {
+        match value {
+            RepeatDef::Loop => AnimationRepeat::Loop,
+            RepeatDef::Cycles(n) => AnimationRepeat::Cycles(n),
+        }
+    }
+}
+
+/// Durations are in milliseconds.
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum DurationDef {
+    PerFrame(u32),
+    PerCycle(u32),
+}
+
+impl From<DurationDef> for AnimationDuration {
+    fn from(value: DurationDef) -> Self {
+        match value {
+            DurationDef::PerFrame(ms) => AnimationDuration::PerFrame(ms),
+            DurationDef::PerCycle(ms) => AnimationDuration::PerCycle(ms),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnimationSetError {
+    #[error("could not read animation set: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse animation set: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("animation \"{name}\" uses frames outside of the {columns}x{rows} atlas")]
+    OutOfBounds {
+        name: String,
+        columns: u32,
+        rows: u32,
+    },
+}
+
+#[derive(Default)]
+struct AnimationSetLoader;
+
+impl AssetLoader for AnimationSetLoader {
+    type Asset = AnimationSet;
+    type Settings = ();
+    type Error = AnimationSetError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let AnimationSetDef { atlas, animations } = AnimationSetDef::parse(&bytes)?;
+
+        let layout = load_context.add_labeled_asset(
+            "layout".to_string(),
+            TextureAtlasLayout::from_grid(
+                atlas.tile_size.into(),
+                atlas.columns,
+                atlas.rows,
+                atlas.padding.map(UVec2::from),
+                atlas.offset.map(UVec2::from),
+            ),
+        );
+        let animations = animations
+            .into_iter()
+            .map(|anim| SetAnimation {
+                stages: anim
+                    .stages
+                    .iter()
+                    .map(|frames| frames.indices(atlas.columns))
+                    .collect(),
+                name: anim.name,
+                repeat: anim.repeat,
+                duration: anim.duration,
+            })
+            .collect();
+        Ok(AnimationSet {
+            layout,
+            texture: None,
+            animations,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+/// Adds the animations of every loaded or hot-reloaded [`AnimationSet`]
+/// to the [`SpritesheetLibrary`] and points [`AnimationNames`] at them.
+/// Sprites that were playing a replaced animation switch to the new one.
+/// The clips and animations they replace are never removed from the library,
+/// so every hot reload leaks a little memory. That only matters while developing.
+fn register_animations(
+    mut events: EventReader<AssetEvent<AnimationSet>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut library: ResMut<SpritesheetLibrary>,
+    mut names: ResMut<AnimationNames>,
+    mut sprite_q: Query<&mut SpritesheetAnimation>,
+) {
+    let mut replaced = HashMap::<AnimationId, AnimationId>::default();
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let Some(set) = animation_sets.get(*id) else {
+            continue;
+        };
+        for anim in &set.animations {
+            let clip_ids = anim
+                .stages
+                .iter()
+                .map(|indices| {
+                    library.new_clip(|clip| {
+                        clip.push_frame_indices(indices.clone());
+                    })
+                })
+                .collect::<Vec<_>>();
+            let anim_id = library.new_animation(|animation| {
+                for &clip_id in &clip_ids {
+                    animation.add_stage(clip_id.into());
+                }
+                animation.set_repeat(anim.repeat.into());
+                if let Some(duration) = anim.duration {
+                    animation.set_duration(duration.into());
+                }
+            });
+            if let Some(old_id) = names.0.insert(anim.name.clone(), anim_id) {
+                replaced.insert(old_id, anim_id);
+            }
+        }
+    }
+    if replaced.is_empty() {
+        return;
+    }
+    for mut sprite in &mut sprite_q {
+        if let Some(&new_id) = replaced.get(&sprite.animation_id) {
+            sprite.animation_id = new_id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A set with a 4x2 atlas and one animation made of `stages`.
+    fn set_with_stages(stages: &str) -> String {
+        format!(
+            "(atlas: (tile_size: (16, 16), columns: 4, rows: 2), \
+             animations: [(name: \"anim\", stages: [{stages}])])"
+        )
+    }
+
+    #[test]
+    fn parses_bundled_sets() {
+        for source in [
+            include_str!("../../../assets/animations/player.anim.ron"),
+            include_str!("../../../assets/animations/bomb.anim.ron"),
+        ] {
+            let set = AnimationSetDef::parse(source.as_bytes()).unwrap();
+            assert!(!set.animations.is_empty());
+        }
+    }
+
+    #[test]
+    fn parses_animation_settings() {
+        let source = include_str!("../../../assets/animations/bomb.anim.ron");
+        let set = AnimationSetDef::parse(source.as_bytes()).unwrap();
+        assert_eq!(set.atlas.tile_size, (16, 16));
+        assert_eq!((set.atlas.columns, set.atlas.rows), (2, 1));
+        let [bomb] = &set.animations[..] else {
+            panic!("expected one animation, got {:?}", set.animations);
+        };
+        assert_eq!(bomb.name, "bomb");
+        assert!(matches!(bomb.repeat, RepeatDef::Cycles(2)));
+        assert!(matches!(bomb.duration, Some(DurationDef::PerFrame(1000))));
+        assert_eq!(bomb.stages[0].indices(set.atlas.columns), vec![0, 1]);
+    }
+
+    #[test]
+    fn defaults_to_looping() {
+        let source = set_with_stages("(row: 0, first: 0, last: 0)");
+        let set = AnimationSetDef::parse(source.as_bytes()).unwrap();
+        assert!(matches!(set.animations[0].repeat, RepeatDef::Loop));
+        assert!(set.animations[0].duration.is_none());
+        assert!(set.atlas.padding.is_none() && set.atlas.offset.is_none());
+    }
+
+    #[test]
+    fn resolves_frames_row_by_row() {
+        let source = set_with_stages("(row: 0, first: 3, last: 3), (row: 1, first: 1, last: 3)");
+        let set = AnimationSetDef::parse(source.as_bytes()).unwrap();
+        let stages = &set.animations[0].stages;
+        assert_eq!(stages[0].indices(4), vec![3]);
+        assert_eq!(stages[1].indices(4), vec![5, 6, 7]);
+    }
+
+    #[test]
+    fn rejects_frames_outside_the_atlas() {
+        for stages in [
+            "(row: 2, first: 0, last: 0)",
+            "(row: 0, first: 0, last: 4)",
+            "(row: 0, first: 2, last: 1)",
+            "(row: 0, first: 0, last: 0), (row: 5, first: 0, last: 0)",
+        ] {
+            let source = set_with_stages(stages);
+            assert!(
+                matches!(
+                    AnimationSetDef::parse(source.as_bytes()),
+                    Err(AnimationSetError::OutOfBounds { columns: 4, rows: 2, ref name }) if name == "anim"
+                ),
+                "{stages}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_sets() {
+        let source = "(atlas: (tile_size: (16, 16), columns: 4), animations: [])";
+        assert!(matches!(
+            AnimationSetDef::parse(source.as_bytes()),
+            Err(AnimationSetError::Ron(_))
+        ));
+    }
+}
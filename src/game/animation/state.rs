@@ -0,0 +1,236 @@
+//! Animation state machines for characters.
+//! A machine picks which animation a sprite plays from its [`AnimationParams`],
+//! blends the playback speed and announces every state change with
+//! [`AnimationStateEntered`] and [`AnimationStateExited`] triggers on the sprite.
+
+use bevy::prelude::*;
+use bevy_spritesheet_animation::component::SpritesheetAnimation;
+
+use super::AnimationNames;
+use crate::{game::facing::Facing, utils::SmoothNudge, AppSet};
+
+/// How quickly the playback speed follows the current state's [`SpeedFactor`].
+const SPEED_BLEND_RATE: f32 = 10.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, update_animation_states.in_set(AppSet::Update));
+}
+
+/// What a character is doing, as far as its animations are concerned.
+/// Written every frame by whatever controls the character.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct AnimationParams {
+    pub velocity: Vec2,
+    pub facing: Facing,
+    pub action: Option<CharacterAction>,
+}
+
+/// Actions that play their own animations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum CharacterAction {
+    Jump,
+    Slide,
+    Attack,
+    HoldItem,
+}
+
+/// Checked against [`AnimationParams`] to decide whether a transition is taken.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub enum Condition {
+    SpeedAbove(f32),
+    SpeedBelow(f32),
+    /// Velocity along `direction` is at least `min`.
+    MovingToward {
+        direction: Vec2,
+        min: f32,
+    },
+    Facing(Facing),
+    Action(CharacterAction),
+    /// The machine has been in its current state for at least this many seconds.
+    InStateFor(f32),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    fn holds(&self, params: &AnimationParams, time_in_state: f32) -> bool {
+        match self {
+            Self::SpeedAbove(speed) => params.velocity.length() > *speed,
+            Self::SpeedBelow(speed) => params.velocity.length() < *speed,
+            Self::MovingToward { direction, min } => {
+                params.velocity.dot(direction.normalize_or_zero()) >= *min
+            }
+            Self::Facing(facing) => params.facing == *facing,
+            Self::Action(action) => params.action == Some(*action),
+            Self::InStateFor(secs) => time_in_state >= *secs,
+            Self::All(conditions) => conditions.iter().all(|c| c.holds(params, time_in_state)),
+            Self::Any(conditions) => conditions.iter().any(|c| c.holds(params, time_in_state)),
+        }
+    }
+}
+
+/// How fast a state's animation plays, relative to its authored duration.
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+pub enum SpeedFactor {
+    Constant(f32),
+    /// Slows down as the character speeds up, reaching `min` at `reference_speed`:
+    /// `min ^ ((speed / reference_speed) ^ sharpness)`.
+    Falloff {
+        reference_speed: f32,
+        min: f32,
+        sharpness: i32,
+    },
+    /// Plays at normal speed when moving at `reference_speed`.
+    Proportional {
+        reference_speed: f32,
+    },
+}
+
+impl SpeedFactor {
+    fn at(&self, speed: f32) -> f32 {
+        match *self {
+            Self::Constant(factor) => factor,
+            Self::Falloff {
+                reference_speed,
+                min,
+                sharpness,
+            } => min.powf((speed / reference_speed).min(1.0).powi(sharpness)),
+            Self::Proportional { reference_speed } => speed / reference_speed,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationState {
+    name: String,
+    /// Name of the animation in [`AnimationNames`].
+    animation: String,
+    speed: SpeedFactor,
+}
+
+impl AnimationState {
+    pub fn new(name: impl Into<String>, animation: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            animation: animation.into(),
+            speed: SpeedFactor::Constant(1.0),
+        }
+    }
+
+    pub fn with_speed(mut self, speed: SpeedFactor) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Transition {
+    /// `None` if the transition can be taken from any state.
+    from: Option<usize>,
+    to: usize,
+    condition: Condition,
+}
+
+/// Picks the animation of a sprite with [`SpritesheetAnimation`] and [`AnimationParams`].
+/// Transitions are checked in the order they were added and the first one whose condition
+/// holds is taken. A transition into the current state keeps it, so later ones are skipped.
+#[derive(Component, Debug, Clone)]
+pub struct AnimationStateMachine {
+    states: Vec<AnimationState>,
+    transitions: Vec<Transition>,
+    current: usize,
+    time_in_state: f32,
+}
+
+impl AnimationStateMachine {
+    /// Starts out in `initial`.
+    pub fn new(initial: AnimationState) -> Self {
+        Self {
+            states: vec![initial],
+            transitions: vec![],
+            current: 0,
+            time_in_state: 0.0,
+        }
+    }
+
+    pub fn with_state(mut self, state: AnimationState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    /// Adds a transition from the state named `from`, or from any state if it's `None`.
+    /// Both states must have been added already.
+    pub fn with_transition(mut self, from: Option<&str>, to: &str, condition: Condition) -> Self {
+        let transition = Transition {
+            from: from.map(|name| self.index_of(name)),
+            to: self.index_of(to),
+            condition,
+        };
+        self.transitions.push(transition);
+        self
+    }
+
+    fn index_of(&self, name: &str) -> usize {
+        self.states
+            .iter()
+            .position(|state| state.name == name)
+            .unwrap_or_else(|| panic!("animation state \"{name}\" does not exist"))
+    }
+
+    fn next_state(&self, params: &AnimationParams) -> usize {
+        self.transitions
+            .iter()
+            .find(|transition| {
+                transition.from.map_or(true, |from| from == self.current)
+                    && transition.condition.holds(params, self.time_in_state)
+            })
+            .map_or(self.current, |transition| transition.to)
+    }
+}
+
+/// Triggered on a sprite when its [`AnimationStateMachine`] enters a state.
+#[derive(Event, Debug, Clone)]
+pub struct AnimationStateEntered(pub String);
+
+/// Triggered on a sprite when its [`AnimationStateMachine`] leaves a state.
+#[derive(Event, Debug, Clone)]
+pub struct AnimationStateExited(pub String);
+
+pub(crate) fn update_animation_states(
+    time: Res<Time>,
+    mut commands: Commands,
+    animation_names: Res<AnimationNames>,
+    mut machine_q: Query<(
+        Entity,
+        &mut AnimationStateMachine,
+        &AnimationParams,
+        &mut SpritesheetAnimation,
+    )>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut machine, params, mut anim) in &mut machine_q {
+        machine.time_in_state += dt;
+        let next = machine.next_state(params);
+        if next != machine.current {
+            let exited = machine.states[machine.current].name.clone();
+            commands.trigger_targets(AnimationStateExited(exited), entity);
+            machine.current = next;
+            machine.time_in_state = 0.0;
+            let entered = machine.states[next].name.clone();
+            commands.trigger_targets(AnimationStateEntered(entered), entity);
+        }
+
+        let state = &machine.states[machine.current];
+        if let Some(id) = animation_names.get(&state.animation) {
+            if id != anim.animation_id {
+                anim.animation_id = id;
+            }
+        }
+        let target = state.speed.at(params.velocity.length());
+        anim.speed_factor
+            .smooth_nudge(&target, SPEED_BLEND_RATE, dt);
+    }
+}
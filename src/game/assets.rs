@@ -1,10 +1,33 @@
+//! Asset handles for the whole game, loaded from `assets/game.manifest.ron`.
+//! Every key must be listed in the manifest, which is checked when it loads.
+
+use std::hash::Hash;
+
 use bevy::{
+    asset::{io::Reader, ron, AssetLoadFailedEvent, AssetLoader, AsyncReadExt, LoadContext},
     prelude::*,
+    reflect::{Enum, TypeInfo, Typed},
     render::texture::{ImageLoaderSettings, ImageSampler},
     utils::HashMap,
 };
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    animation::AnimationSet, cutscene::Cutscene, dialogue::Dialogue, inventory::ItemCatalog,
+    quest::QuestCatalog, shop::ShopCatalog,
+};
+
+/// Path of the manifest, relative to the `assets` folder.
+const MANIFEST_PATH: &str = "game.manifest.ron";
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AssetManifest>();
+    app.init_asset_loader::<AssetManifestLoader>();
+    app.init_resource::<ManifestHandle>();
+    app.add_systems(Update, (apply_manifest, report_failed_assets).chain());
+    app.observe(retry_failed_assets);
+
     app.register_type::<HandleMap<ImageKey>>();
     app.init_resource::<HandleMap<ImageKey>>();
 
@@ -13,9 +36,22 @@ pub(super) fn plugin(app: &mut App) {
 
     app.register_type::<HandleMap<SoundtrackKey>>();
     app.init_resource::<HandleMap<SoundtrackKey>>();
+
+    app.register_type::<HandleMap<AnimationKey>>();
+    app.init_resource::<HandleMap<AnimationKey>>();
+
+    app.register_type::<HandleMap<DialogueKey>>();
+    app.init_resource::<HandleMap<DialogueKey>>();
+
+    app.register_type::<HandleMap<CutsceneKey>>();
+    app.init_resource::<HandleMap<CutsceneKey>>();
+
+    app.init_resource::<ItemCatalogHandle>();
+    app.init_resource::<ShopCatalogHandle>();
+    app.init_resource::<QuestCatalogHandle>();
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
 pub enum ImageKey {
     Ducky,
     Player,
@@ -26,43 +62,7 @@ impl AssetKey for ImageKey {
     type Asset = Image;
 }
 
-impl FromWorld for HandleMap<ImageKey> {
-    fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        [
-            (
-                ImageKey::Ducky,
-                asset_server.load_with_settings(
-                    "images/ducky.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-            ),
-            (
-                ImageKey::Player,
-                asset_server.load_with_settings(
-                    "images/sheet.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-            ),
-            (
-                ImageKey::Bomb,
-                asset_server.load_with_settings(
-                    "images/bomb.png",
-                    |settings: &mut ImageLoaderSettings| {
-                        settings.sampler = ImageSampler::nearest();
-                    },
-                ),
-            ),
-        ]
-        .into()
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
 pub enum SfxKey {
     ButtonHover,
     ButtonPress,
@@ -70,34 +70,15 @@ pub enum SfxKey {
     Step2,
     Step3,
     Step4,
+    ItemGet,
+    Pickup,
 }
 
 impl AssetKey for SfxKey {
     type Asset = AudioSource;
 }
 
-impl FromWorld for HandleMap<SfxKey> {
-    fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        [
-            (
-                SfxKey::ButtonHover,
-                asset_server.load("audio/sfx/button_hover.ogg"),
-            ),
-            (
-                SfxKey::ButtonPress,
-                asset_server.load("audio/sfx/button_press.ogg"),
-            ),
-            (SfxKey::Step1, asset_server.load("audio/sfx/step1.ogg")),
-            (SfxKey::Step2, asset_server.load("audio/sfx/step2.ogg")),
-            (SfxKey::Step3, asset_server.load("audio/sfx/step3.ogg")),
-            (SfxKey::Step4, asset_server.load("audio/sfx/step4.ogg")),
-        ]
-        .into()
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
 pub enum SoundtrackKey {
     Credits,
     GoingIn,
@@ -112,37 +93,32 @@ impl AssetKey for SoundtrackKey {
     type Asset = AudioSource;
 }
 
-impl FromWorld for HandleMap<SoundtrackKey> {
-    fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        [
-            (
-                SoundtrackKey::GoingIn,
-                asset_server.load("audio/soundtracks/GOING IN.mp3"),
-            ),
-            (
-                SoundtrackKey::Worldwid3,
-                asset_server.load("audio/soundtracks/Worldwid3.mp3"),
-            ),
-            (
-                SoundtrackKey::BigM,
-                asset_server.load("audio/soundtracks/BIG MUNITIONS.mp3"),
-            ),
-            (
-                SoundtrackKey::Izo,
-                asset_server.load("audio/soundtracks/IZO.mp3"),
-            ),
-            (
-                SoundtrackKey::Squirrels,
-                asset_server.load("audio/soundtracks/SQUIRRELS.mp3"),
-            ),
-            (
-                SoundtrackKey::Usokoto,
-                asset_server.load("audio/soundtracks/USOKOTO.mp3"),
-            ),
-        ]
-        .into()
-    }
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
+pub enum AnimationKey {
+    Player,
+    Bomb,
+}
+
+impl AssetKey for AnimationKey {
+    type Asset = AnimationSet;
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
+pub enum DialogueKey {
+    Villager,
+}
+
+impl AssetKey for DialogueKey {
+    type Asset = Dialogue;
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
+pub enum CutsceneKey {
+    Intro,
+}
+
+impl AssetKey for CutsceneKey {
+    type Asset = Cutscene;
 }
 
 pub trait AssetKey: Sized {
@@ -162,9 +138,405 @@ where
     }
 }
 
+/// Starts out empty until the [`AssetManifest`] has been loaded.
+impl<K: AssetKey> Default for HandleMap<K> {
+    fn default() -> Self {
+        Self(HashMap::default())
+    }
+}
+
 impl<K: AssetKey> HandleMap<K> {
     pub fn all_loaded(&self, asset_server: &AssetServer) -> bool {
         self.values()
             .all(|x| asset_server.is_loaded_with_dependencies(x))
     }
+
+    /// Find the key an asset was loaded for.
+    pub fn key_of(&self, id: AssetId<K::Asset>) -> Option<&K> {
+        self.iter()
+            .find(|(_, handle)| handle.id() == id)
+            .map(|(key, _)| key)
+    }
+}
+
+/// The game's only [`ItemCatalog`], which is empty until the [`AssetManifest`] has been loaded.
+#[derive(Resource, Default, Debug)]
+pub struct ItemCatalogHandle(pub Handle<ItemCatalog>);
+
+/// The game's only [`ShopCatalog`], which is empty until the [`AssetManifest`] has been loaded.
+#[derive(Resource, Default, Debug)]
+pub struct ShopCatalogHandle(pub Handle<ShopCatalog>);
+
+/// The game's only [`QuestCatalog`], which is empty until the [`AssetManifest`] has been loaded.
+#[derive(Resource, Default, Debug)]
+pub struct QuestCatalogHandle(pub Handle<QuestCatalog>);
+
+/// Declares which file, and with which settings, every asset key is loaded from.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct AssetManifest {
+    pub images: HashMap<ImageKey, ImageEntry>,
+    pub sfx: HashMap<SfxKey, AudioEntry>,
+    pub soundtracks: HashMap<SoundtrackKey, AudioEntry>,
+    pub animations: HashMap<AnimationKey, AnimationEntry>,
+    pub dialogue: HashMap<DialogueKey, DialogueEntry>,
+    pub cutscenes: HashMap<CutsceneKey, CutsceneEntry>,
+    pub items: DataEntry,
+    pub shops: DataEntry,
+    pub quests: DataEntry,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageEntry {
+    pub path: String,
+    #[serde(default)]
+    pub sampler: Sampler,
+}
+
+/// Texture sampling used for an image. Pixel art wants [`Sampler::Nearest`].
+#[derive(Copy, Clone, Debug, Default, Deserialize)]
+pub enum Sampler {
+    #[default]
+    Nearest,
+    Linear,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AudioEntry {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnimationEntry {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DialogueEntry {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CutsceneEntry {
+    pub path: String,
+}
+
+/// A data file there's only one of, so it isn't looked up by key.
+#[derive(Debug, Deserialize)]
+pub struct DataEntry {
+    pub path: String,
+}
+
+/// An entry in the [`AssetManifest`] that knows how to load its asset.
+trait ManifestEntry<A: Asset> {
+    fn load(&self, asset_server: &AssetServer) -> Handle<A>;
+}
+
+impl ManifestEntry<Image> for ImageEntry {
+    fn load(&self, asset_server: &AssetServer) -> Handle<Image> {
+        let sampler = self.sampler;
+        asset_server.load_with_settings(
+            self.path.clone(),
+            move |settings: &mut ImageLoaderSettings| {
+                settings.sampler = match sampler {
+                    Sampler::Nearest => ImageSampler::nearest(),
+                    Sampler::Linear => ImageSampler::linear(),
+                };
+            },
+        )
+    }
+}
+
+impl ManifestEntry<AudioSource> for AudioEntry {
+    fn load(&self, asset_server: &AssetServer) -> Handle<AudioSource> {
+        asset_server.load(self.path.clone())
+    }
+}
+
+impl ManifestEntry<AnimationSet> for AnimationEntry {
+    fn load(&self, asset_server: &AssetServer) -> Handle<AnimationSet> {
+        asset_server.load(self.path.clone())
+    }
+}
+
+impl ManifestEntry<Dialogue> for DialogueEntry {
+    fn load(&self, asset_server: &AssetServer) -> Handle<Dialogue> {
+        asset_server.load(self.path.clone())
+    }
+}
+
+impl ManifestEntry<Cutscene> for CutsceneEntry {
+    fn load(&self, asset_server: &AssetServer) -> Handle<Cutscene> {
+        asset_server.load(self.path.clone())
+    }
+}
+
+impl AssetManifest {
+    /// Names of all keys that have no entry in the manifest, prefixed with their section.
+    fn missing_keys(&self) -> Vec<String> {
+        let mut missing = missing_keys("images", &self.images);
+        missing.extend(missing_keys("sfx", &self.sfx));
+        missing.extend(missing_keys("soundtracks", &self.soundtracks));
+        missing.extend(missing_keys("animations", &self.animations));
+        missing.extend(missing_keys("dialogue", &self.dialogue));
+        missing.extend(missing_keys("cutscenes", &self.cutscenes));
+        missing
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+    fn paths(&self) -> impl Iterator<Item = &str> {
+        let images = self.images.values().map(|e| e.path.as_str());
+        let sfx = self.sfx.values().map(|e| e.path.as_str());
+        let soundtracks = self.soundtracks.values().map(|e| e.path.as_str());
+        let animations = self.animations.values().map(|e| e.path.as_str());
+        let dialogue = self.dialogue.values().map(|e| e.path.as_str());
+        let cutscenes = self.cutscenes.values().map(|e| e.path.as_str());
+        images
+            .chain(sfx)
+            .chain(soundtracks)
+            .chain(animations)
+            .chain(dialogue)
+            .chain(cutscenes)
+            .chain([
+                self.items.path.as_str(),
+                self.shops.path.as_str(),
+                self.quests.path.as_str(),
+            ])
+    }
+}
+
+fn missing_keys<K: Enum + Typed, E>(section: &str, entries: &HashMap<K, E>) -> Vec<String> {
+    let TypeInfo::Enum(info) = K::type_info() else {
+        return vec![];
+    };
+    info.variant_names()
+        .iter()
+        .filter(|name| !entries.keys().any(|key| key.variant_name() == **name))
+        .map(|name| format!("{section}.{name}"))
+        .collect()
+}
+
+fn handle_map<K, E>(entries: &HashMap<K, E>, asset_server: &AssetServer) -> HandleMap<K>
+where
+    K: AssetKey + Copy + Eq + Hash,
+    E: ManifestEntry<K::Asset>,
+{
+    entries
+        .iter()
+        .map(|(key, entry)| (*key, entry.load(asset_server)))
+        .collect::<HashMap<_, _>>()
+        .into()
+}
+
+#[derive(Debug, Error)]
+pub enum AssetManifestError {
+    #[error("could not read asset manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse asset manifest: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("asset manifest has no entry for: {}", .0.join(", "))]
+    MissingKeys(Vec<String>),
+}
+
+#[derive(Default)]
+struct AssetManifestLoader;
+
+impl AssetLoader for AssetManifestLoader {
+    type Asset = AssetManifest;
+    type Settings = ();
+    type Error = AssetManifestError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let manifest: AssetManifest = ron::de::from_bytes(&bytes)?;
+        let missing = manifest.missing_keys();
+        if !missing.is_empty() {
+            return Err(AssetManifestError::MissingKeys(missing));
+        }
+        Ok(manifest)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
+
+/// The loaded [`AssetManifest`] and whether the [`HandleMap`]s were built from it.
+#[derive(Resource)]
+pub struct ManifestHandle {
+    handle: Handle<AssetManifest>,
+    applied: bool,
+}
+
+impl FromWorld for ManifestHandle {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            handle: world.resource::<AssetServer>().load(MANIFEST_PATH),
+            applied: false,
+        }
+    }
+}
+
+impl ManifestHandle {
+    /// Whether the [`HandleMap`]s are filled in and can be indexed by any key.
+    pub fn is_applied(&self) -> bool {
+        self.applied
+    }
+
+    pub fn handle(&self) -> &Handle<AssetManifest> {
+        &self.handle
+    }
+}
+
+/// (Re)builds the [`HandleMap`]s whenever the manifest is loaded or hot-reloaded.
+fn apply_manifest(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<AssetManifest>>,
+    mut manifest_handle: ResMut<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    asset_server: Res<AssetServer>,
+) {
+    let id = manifest_handle.handle.id();
+    let changed = events.read().any(|event| {
+        matches!(event,
+            AssetEvent::LoadedWithDependencies { id: e } | AssetEvent::Modified { id: e }
+                if *e == id)
+    });
+    if !changed {
+        return;
+    }
+    let Some(manifest) = manifests.get(id) else {
+        return;
+    };
+
+    #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+    {
+        let root = bevy::asset::io::file::FileAssetReader::get_base_path().join("assets");
+        for path in manifest.paths() {
+            if !root.join(path).exists() {
+                error!("asset manifest lists \"{path}\", which does not exist");
+            }
+        }
+    }
+
+    load_manifest_entries(&mut commands, manifest, &asset_server);
+    manifest_handle.applied = true;
+}
+
+/// Loads every entry of the manifest with its settings and stores the handles.
+/// Entries that are already loaded keep their handle and aren't loaded again.
+fn load_manifest_entries(
+    commands: &mut Commands,
+    manifest: &AssetManifest,
+    asset_server: &AssetServer,
+) {
+    commands.insert_resource(handle_map(&manifest.images, &asset_server));
+    commands.insert_resource(handle_map(&manifest.sfx, &asset_server));
+    commands.insert_resource(handle_map(&manifest.soundtracks, &asset_server));
+    commands.insert_resource(handle_map(&manifest.animations, &asset_server));
+    commands.insert_resource(handle_map(&manifest.dialogue, &asset_server));
+    commands.insert_resource(handle_map(&manifest.cutscenes, &asset_server));
+    commands.insert_resource(ItemCatalogHandle(
+        asset_server.load(manifest.items.path.clone()),
+    ));
+    commands.insert_resource(ShopCatalogHandle(
+        asset_server.load(manifest.shops.path.clone()),
+    ));
+    commands.insert_resource(QuestCatalogHandle(
+        asset_server.load(manifest.quests.path.clone()),
+    ));
+}
+
+/// Trigger this event to load the assets that failed to load once more.
+#[derive(Event, Debug)]
+pub struct RetryFailedAssets;
+
+fn retry_failed_assets(
+    _trigger: Trigger<RetryFailedAssets>,
+    mut commands: Commands,
+    manifest_handle: Res<ManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    asset_server: Res<AssetServer>,
+) {
+    match manifests.get(manifest_handle.handle.id()) {
+        // Loading the entries again uses the same settings as the first time,
+        // unlike reloading them by path.
+        Some(manifest) => load_manifest_entries(&mut commands, manifest, &asset_server),
+        None => asset_server.reload(MANIFEST_PATH),
+    }
+}
+
+fn report_failed_assets(
+    mut image_events: EventReader<AssetLoadFailedEvent<Image>>,
+    mut audio_events: EventReader<AssetLoadFailedEvent<AudioSource>>,
+    mut animation_events: EventReader<AssetLoadFailedEvent<AnimationSet>>,
+    mut dialogue_events: EventReader<AssetLoadFailedEvent<Dialogue>>,
+    mut cutscene_events: EventReader<AssetLoadFailedEvent<Cutscene>>,
+    mut item_events: EventReader<AssetLoadFailedEvent<ItemCatalog>>,
+    mut shop_events: EventReader<AssetLoadFailedEvent<ShopCatalog>>,
+    mut quest_events: EventReader<AssetLoadFailedEvent<QuestCatalog>>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    sfx_handles: Res<HandleMap<SfxKey>>,
+    soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    animation_handles: Res<HandleMap<AnimationKey>>,
+    dialogue_handles: Res<HandleMap<DialogueKey>>,
+    cutscene_handles: Res<HandleMap<CutsceneKey>>,
+    item_catalog: Res<ItemCatalogHandle>,
+    shop_catalog: Res<ShopCatalogHandle>,
+    quest_catalog: Res<QuestCatalogHandle>,
+) {
+    for event in image_events.read() {
+        if let Some(key) = image_handles.key_of(event.id) {
+            error!("image {key:?} failed to load from \"{}\"", event.path);
+        }
+    }
+    for event in audio_events.read() {
+        if let Some(key) = sfx_handles.key_of(event.id) {
+            error!(
+                "sound effect {key:?} failed to load from \"{}\"",
+                event.path
+            );
+        }
+        if let Some(key) = soundtrack_handles.key_of(event.id) {
+            error!("soundtrack {key:?} failed to load from \"{}\"", event.path);
+        }
+    }
+    for event in animation_events.read() {
+        if let Some(key) = animation_handles.key_of(event.id) {
+            error!(
+                "animation set {key:?} failed to load from \"{}\"",
+                event.path
+            );
+        }
+    }
+    for event in dialogue_events.read() {
+        if let Some(key) = dialogue_handles.key_of(event.id) {
+            error!("dialogue {key:?} failed to load from \"{}\"", event.path);
+        }
+    }
+    for event in cutscene_events.read() {
+        if let Some(key) = cutscene_handles.key_of(event.id) {
+            error!("cutscene {key:?} failed to load from \"{}\"", event.path);
+        }
+    }
+    for event in item_events.read() {
+        if event.id == item_catalog.0.id() {
+            error!("item catalog failed to load from \"{}\"", event.path);
+        }
+    }
+    for event in shop_events.read() {
+        if event.id == shop_catalog.0.id() {
+            error!("shop catalog failed to load from \"{}\"", event.path);
+        }
+    }
+    for event in quest_events.read() {
+        if event.id == quest_catalog.0.id() {
+            error!("quest catalog failed to load from \"{}\"", event.path);
+        }
+    }
 }
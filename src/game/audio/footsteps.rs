@@ -0,0 +1,112 @@
+//! How footsteps sound on every [`GroundMaterial`]. There are only generic step
+//! recordings so far, so materials share the same clips and are told apart by
+//! the pitch and volume they're played at.
+
+use bevy::{prelude::*, utils::HashMap};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::game::{assets::SfxKey, ground::GroundMaterial};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FootstepVariations>();
+    app.init_resource::<FootstepVariations>();
+}
+
+/// The pitch and volume of steps on one ground material, and the clips they're picked from.
+#[derive(Clone, Debug, Reflect)]
+pub struct FootstepVariation {
+    pub clips: Vec<SfxKey>,
+    /// Base playback speed, which also shifts the pitch.
+    pub pitch: f32,
+    /// Maximum random deviation from [`Self::pitch`].
+    pub pitch_variation: f32,
+    pub volume: f32,
+    /// Maximum random deviation from [`Self::volume`].
+    pub volume_variation: f32,
+    /// Last clip that was played, so the same one is not picked twice in a row.
+    last: Option<SfxKey>,
+}
+
+impl FootstepVariation {
+    pub fn new(clips: impl Into<Vec<SfxKey>>) -> Self {
+        Self {
+            clips: clips.into(),
+            pitch: 1.0,
+            pitch_variation: 0.08,
+            volume: 1.0,
+            volume_variation: 0.15,
+            last: None,
+        }
+    }
+
+    pub fn with_pitch(mut self, pitch: f32, variation: f32) -> Self {
+        self.pitch = pitch;
+        self.pitch_variation = variation;
+        self
+    }
+
+    pub fn with_volume(mut self, volume: f32, variation: f32) -> Self {
+        self.volume = volume;
+        self.volume_variation = variation;
+        self
+    }
+
+    /// Pick a random clip that differs from the previous one,
+    /// together with a randomized pitch and volume.
+    pub fn pick(&mut self) -> Option<(SfxKey, f32, f32)> {
+        let mut rng = rand::thread_rng();
+        let candidates = self
+            .clips
+            .iter()
+            .copied()
+            .filter(|key| Some(*key) != self.last || self.clips.len() == 1)
+            .collect::<Vec<_>>();
+        let key = *candidates.choose(&mut rng)?;
+        self.last = Some(key);
+
+        let (pitch_variation, volume_variation) =
+            (self.pitch_variation.abs(), self.volume_variation.abs());
+        let pitch = self.pitch + rng.gen_range(-pitch_variation..=pitch_variation);
+        let volume = self.volume + rng.gen_range(-volume_variation..=volume_variation);
+        Some((key, pitch.max(0.01), volume.max(0.0)))
+    }
+}
+
+/// Footstep variations keyed by the material that is stepped on.
+#[derive(Resource, Reflect, Deref, DerefMut)]
+#[reflect(Resource)]
+pub struct FootstepVariations(HashMap<GroundMaterial, FootstepVariation>);
+
+impl Default for FootstepVariations {
+    fn default() -> Self {
+        let steps = [SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4];
+        Self(
+            [
+                (GroundMaterial::Grass, FootstepVariation::new(steps)),
+                (
+                    GroundMaterial::Stone,
+                    FootstepVariation::new(steps).with_pitch(1.25, 0.05),
+                ),
+                (
+                    GroundMaterial::Wood,
+                    FootstepVariation::new(steps)
+                        .with_pitch(0.9, 0.05)
+                        .with_volume(1.1, 0.1),
+                ),
+                (
+                    GroundMaterial::Water,
+                    FootstepVariation::new(steps)
+                        .with_pitch(0.6, 0.12)
+                        .with_volume(0.8, 0.2),
+                ),
+                (
+                    GroundMaterial::Sand,
+                    FootstepVariation::new(steps)
+                        .with_pitch(0.75, 0.1)
+                        .with_volume(0.6, 0.15),
+                ),
+            ]
+            .into(),
+        )
+    }
+}
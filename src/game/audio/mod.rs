@@ -1,8 +1,9 @@
+pub mod footsteps;
 pub mod sfx;
 pub mod soundtrack;
 
 use bevy::prelude::*;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((sfx::plugin, soundtrack::plugin));
+    app.add_plugins((footsteps::plugin, sfx::plugin, soundtrack::plugin));
 }
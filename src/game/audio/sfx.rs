@@ -1,40 +1,116 @@
-use bevy::{audio::PlaybackMode, prelude::*};
-use rand::seq::SliceRandom;
+use bevy::{
+    audio::{PlaybackMode, SpatialScale, Volume},
+    prelude::*,
+};
 
-use crate::game::assets::{HandleMap, SfxKey};
+use super::footsteps::FootstepVariations;
+use crate::{
+    game::{
+        assets::{HandleMap, SfxKey},
+        ground::Footing,
+    },
+    AppSet,
+};
+
+/// Distance in world units within which positional sounds play at full volume.
+/// Beyond it, volume falls off with the inverse square of the distance.
+const SFX_FULL_VOLUME_DISTANCE: f32 = 160.0;
+/// Distance in world units between the ears of the [`SpatialListener`].
+/// Larger values give a softer left/right balance.
+pub const LISTENER_EAR_GAP: f32 = 320.0;
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SfxEmitter>();
     app.observe(play_sfx);
+    app.add_systems(Update, follow_sfx_emitter.in_set(AppSet::Update));
 }
 
 fn play_sfx(
     trigger: Trigger<PlaySfx>,
     mut commands: Commands,
     sfx_handles: Res<HandleMap<SfxKey>>,
+    mut footstep_variations: ResMut<FootstepVariations>,
+    emitter_q: Query<&GlobalTransform>,
+    footing_q: Query<&Footing>,
 ) {
-    let sfx_key = match trigger.event() {
-        PlaySfx::Key(key) => *key,
-        PlaySfx::RandomStep => random_step(),
+    let (sfx_key, position, speed, volume) = match trigger.event() {
+        PlaySfx::Key(key) => (*key, None, 1.0, 1.0),
+        PlaySfx::Footstep => {
+            let material = footing_q
+                .get(trigger.entity())
+                .map(|footing| **footing)
+                .unwrap_or_default();
+            let Some((key, speed, volume)) = footstep_variations
+                .get_mut(&material)
+                .and_then(|variation| variation.pick())
+            else {
+                return;
+            };
+            (key, None, speed, volume)
+        }
+        PlaySfx::At(key, position) => (*key, Some(*position), 1.0, 1.0),
+    };
+    // Sounds triggered on an entity are played from that entity's position.
+    let emitter = emitter_q
+        .get(trigger.entity())
+        .ok()
+        .map(|xf| (trigger.entity(), xf.translation().xy()));
+    let position = position.or(emitter.map(|(_, pos)| pos));
+    // Missing until the asset manifest has loaded.
+    let Some(handle) = sfx_handles.get(&sfx_key) else {
+        return;
     };
-    commands.spawn(AudioSourceBundle {
-        source: sfx_handles[&sfx_key].clone_weak(),
+
+    let mut sfx = commands.spawn(AudioSourceBundle {
+        source: handle.clone_weak(),
         settings: PlaybackSettings {
             mode: PlaybackMode::Despawn,
+            volume: Volume::new(volume),
+            speed,
+            spatial: position.is_some(),
+            spatial_scale: Some(SpatialScale::new_2d(1.0 / SFX_FULL_VOLUME_DISTANCE)),
             ..default()
         },
     });
+    if let Some(position) = position {
+        sfx.insert(TransformBundle::from_transform(
+            Transform::from_translation(position.extend(0.0)),
+        ));
+    }
+    if let Some((entity, _)) = emitter {
+        sfx.insert(SfxEmitter(entity));
+    }
+}
+
+/// Keeps a positional sound effect on top of the entity that emitted it.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SfxEmitter(Entity);
+
+fn follow_sfx_emitter(
+    mut commands: Commands,
+    mut sfx_q: Query<(Entity, &SfxEmitter, &mut Transform)>,
+    emitter_q: Query<&GlobalTransform, Without<SfxEmitter>>,
+) {
+    for (entity, SfxEmitter(emitter), mut xf) in &mut sfx_q {
+        match emitter_q.get(*emitter) {
+            Ok(emitter_xf) => xf.translation = emitter_xf.translation().xy().extend(0.0),
+            // The emitter is gone, keep playing from where it was last seen.
+            Err(_) => {
+                commands.entity(entity).remove::<SfxEmitter>();
+            }
+        }
+    }
 }
 
 /// Trigger this event to play a single sound effect.
+/// Trigger it with [`Commands::trigger_targets`] on an entity to
+/// play it from that entity's position relative to the [`SpatialListener`].
 #[derive(Event)]
 pub enum PlaySfx {
     Key(SfxKey),
-    RandomStep,
-}
-
-fn random_step() -> SfxKey {
-    [SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4]
-        .choose(&mut rand::thread_rng())
-        .copied()
-        .unwrap()
+    /// Play a step from the [`FootstepVariations`] entry of the emitter's [`Footing`].
+    Footstep,
+    /// Play a sound effect from a fixed world position.
+    At(SfxKey, Vec2),
 }
@@ -1,74 +1,349 @@
-use bevy::{audio::PlaybackMode, prelude::*};
+use avian2d::collision::CollidingEntities;
+use bevy::{
+    asset::LoadState,
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+    utils::HashMap,
+};
 use rand::seq::SliceRandom;
+use serde::Deserialize;
 
-const GAME_MUSIC: [SoundtrackKey; 4] = [
-    SoundtrackKey::GoingIn,
-    SoundtrackKey::Worldwid3,
-    SoundtrackKey::BigM,
-    SoundtrackKey::Izo,
-];
-const MENU_MUSIC: [SoundtrackKey; 2] = [SoundtrackKey::Usokoto, SoundtrackKey::Squirrels];
 use crate::{
-    game::assets::{HandleMap, SoundtrackKey},
-    screen::Screen,
+    game::{
+        assets::{HandleMap, SoundtrackKey},
+        spawn::player::Player,
+    },
     AppSet,
 };
 
+/// Volume a soundtrack fades in to.
+const MUSIC_VOLUME: f32 = 1.0;
+/// Default duration of a crossfade between two tracks, in seconds.
+const CROSSFADE_SECS: f32 = 2.0;
+
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<IsSoundtrack>();
+    app.register_type::<MusicFade>();
+    app.register_type::<MusicArea>();
+    app.register_type::<MusicManager>();
+    app.register_type::<Playlists>();
+    app.init_resource::<MusicManager>();
+    app.init_resource::<Playlists>();
     app.observe(play_soundtrack);
-    app.add_systems(Update, shuffle_song.in_set(AppSet::Update));
+    app.add_systems(Update, tick_music_fade.in_set(AppSet::TickTimers));
+    app.add_systems(
+        Update,
+        (update_music_area, advance_playlist)
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// A named list of tracks that is shuffled through while it is active.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
+pub enum PlaylistKey {
+    Menu,
+    Game,
+    Dungeon,
+}
+
+/// The tracks belonging to every [`PlaylistKey`].
+#[derive(Resource, Reflect, Deref, DerefMut)]
+#[reflect(Resource)]
+pub struct Playlists(HashMap<PlaylistKey, Vec<SoundtrackKey>>);
+
+impl Default for Playlists {
+    fn default() -> Self {
+        Self(
+            [
+                (
+                    PlaylistKey::Menu,
+                    vec![SoundtrackKey::Usokoto, SoundtrackKey::Squirrels],
+                ),
+                (
+                    PlaylistKey::Game,
+                    vec![
+                        SoundtrackKey::GoingIn,
+                        SoundtrackKey::Worldwid3,
+                        SoundtrackKey::BigM,
+                        SoundtrackKey::Izo,
+                    ],
+                ),
+                (PlaylistKey::Dungeon, vec![SoundtrackKey::BigM]),
+            ]
+            .into(),
+        )
+    }
+}
+
+/// Keeps track of which playlist should be heard and what was played last.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct MusicManager {
+    /// Playlist requested by the current screen.
+    pub playlist: Option<PlaylistKey>,
+    /// Playlist requested by the [`MusicArea`] the player is standing in.
+    /// Takes priority over [`Self::playlist`].
+    pub area: Option<PlaylistKey>,
+    /// Crossfade duration in seconds.
+    pub crossfade: f32,
+    /// Playlist the current track was picked from.
+    playing_from: Option<PlaylistKey>,
+    /// Whether the current track was requested by key. It keeps playing over the
+    /// [`Self::area`] playlist until the player enters or leaves an area.
+    keyed: bool,
+    /// Last track that was started, used to avoid immediate repeats.
+    last: Option<SoundtrackKey>,
+}
+
+impl Default for MusicManager {
+    fn default() -> Self {
+        Self {
+            playlist: None,
+            area: None,
+            crossfade: CROSSFADE_SECS,
+            playing_from: None,
+            keyed: false,
+            last: None,
+        }
+    }
+}
+
+impl MusicManager {
+    /// The playlist that should currently be heard.
+    pub fn active_playlist(&self) -> Option<PlaylistKey> {
+        self.area.or(self.playlist)
+    }
+
+    /// Pick a random track from `tracks`, avoiding the one that was played last
+    /// unless it is the only choice.
+    fn pick_next(&self, tracks: &[SoundtrackKey]) -> Option<SoundtrackKey> {
+        let candidates = tracks
+            .iter()
+            .copied()
+            .filter(|key| Some(*key) != self.last)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            tracks.choose(&mut rand::thread_rng()).copied()
+        } else {
+            candidates.choose(&mut rand::thread_rng()).copied()
+        }
+    }
+}
+
+/// A region of the level that switches the music to another playlist
+/// while the player is inside it.
+/// Needs a [`Sensor`](avian2d::collision::Sensor) collider and [`CollidingEntities`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MusicArea(pub PlaylistKey);
+
+/// Fades the volume of a soundtrack entity from `from` to `to`.
+/// Entities that fade out to silence are despawned once the fade finishes.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct MusicFade {
+    timer: Timer,
+    from: f32,
+    to: f32,
+}
+
+impl MusicFade {
+    fn new(from: f32, to: f32, secs: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(secs.max(f32::EPSILON), TimerMode::Once),
+            from,
+            to,
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        self.from.lerp(self.to, self.timer.fraction())
+    }
+}
+
+fn tick_music_fade(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut fade_q: Query<(Entity, &mut MusicFade, Option<&AudioSink>)>,
+) {
+    for (entity, mut fade, sink) in &mut fade_q {
+        fade.timer.tick(time.delta());
+        if let Some(sink) = sink {
+            sink.set_volume(fade.volume());
+        }
+        if !fade.timer.finished() {
+            continue;
+        }
+        if fade.to <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            commands.entity(entity).remove::<MusicFade>();
+        }
+    }
 }
 
-fn shuffle_song(mut commands: Commands, q: Query<&IsSoundtrack>, state: Res<State<Screen>>) {
-    if q.iter().len() != 0 {
+fn update_music_area(
+    mut manager: ResMut<MusicManager>,
+    area_q: Query<(&MusicArea, &CollidingEntities)>,
+    player_q: Query<(), With<Player>>,
+) {
+    let area = area_q
+        .iter()
+        .find(|(_, colliding)| colliding.iter().any(|&e| player_q.contains(e)))
+        .map(|(MusicArea(key), _)| *key);
+    if manager.area != area {
+        manager.area = area;
+        manager.keyed = false;
+    }
+}
+
+/// Starts the next track when the current one ends
+/// or when the active playlist changes.
+fn advance_playlist(
+    mut commands: Commands,
+    mut manager: ResMut<MusicManager>,
+    playlists: Res<Playlists>,
+    asset_server: Res<AssetServer>,
+    soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    soundtrack_query: Query<(Entity, Option<&AudioSink>), With<IsSoundtrack>>,
+) {
+    let active = manager.active_playlist();
+    if !soundtrack_query.is_empty() && (manager.keyed || active == manager.playing_from) {
         return;
     }
-    let Some(&key) = (match state.get() {
-        Screen::Title => MENU_MUSIC.as_slice().choose(&mut rand::thread_rng()),
-        Screen::Playing => GAME_MUSIC.as_slice().choose(&mut rand::thread_rng()),
-        _ => return,
-    }) else {
+    let Some(active) = active else {
         return;
     };
-    commands.trigger(PlaySoundtrack::Key(key));
+    // A track that can't be played would never end, so the playlist would stall on it.
+    let tracks = playlists.get(&active).map_or_else(Vec::new, |tracks| {
+        tracks
+            .iter()
+            .copied()
+            .filter(|key| playable_handle(&asset_server, &soundtrack_handles, *key).is_some())
+            .collect()
+    });
+    let Some(key) = manager.pick_next(&tracks) else {
+        return;
+    };
+    manager.playing_from = Some(active);
+    manager.keyed = false;
+    crossfade_to(
+        &mut commands,
+        &mut manager,
+        &asset_server,
+        &soundtrack_handles,
+        &soundtrack_query,
+        Some(key),
+    );
 }
+
 fn play_soundtrack(
     trigger: Trigger<PlaySoundtrack>,
     mut commands: Commands,
+    mut manager: ResMut<MusicManager>,
+    asset_server: Res<AssetServer>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
-    soundtrack_query: Query<Entity, With<IsSoundtrack>>,
+    soundtrack_query: Query<(Entity, Option<&AudioSink>), With<IsSoundtrack>>,
+) {
+    let soundtrack_key = match trigger.event() {
+        PlaySoundtrack::Key(key) => {
+            manager.playlist = None;
+            manager.playing_from = None;
+            manager.keyed = true;
+            Some(*key)
+        }
+        PlaySoundtrack::Playlist(playlist) => {
+            if manager.playlist == Some(*playlist) && !manager.keyed {
+                return;
+            }
+            // The next track is picked by `advance_playlist`.
+            manager.playlist = Some(*playlist);
+            manager.keyed = false;
+            return;
+        }
+        PlaySoundtrack::Disable => {
+            manager.playlist = None;
+            manager.area = None;
+            manager.playing_from = None;
+            manager.keyed = false;
+            None
+        }
+    };
+    crossfade_to(
+        &mut commands,
+        &mut manager,
+        &asset_server,
+        &soundtrack_handles,
+        &soundtrack_query,
+        soundtrack_key,
+    );
+}
+
+/// Fade out every playing soundtrack and fade in `key`, if any.
+fn crossfade_to(
+    commands: &mut Commands,
+    manager: &mut MusicManager,
+    asset_server: &AssetServer,
+    soundtrack_handles: &HandleMap<SoundtrackKey>,
+    soundtrack_query: &Query<(Entity, Option<&AudioSink>), With<IsSoundtrack>>,
+    key: Option<SoundtrackKey>,
 ) {
-    for entity in &soundtrack_query {
-        commands.entity(entity).despawn_recursive();
+    let crossfade = manager.crossfade;
+    // Fade out from where each track is, since it may still be fading in.
+    for (entity, sink) in soundtrack_query {
+        let volume = sink.map_or(0.0, |sink| sink.volume());
+        commands
+            .entity(entity)
+            .remove::<IsSoundtrack>()
+            .insert(MusicFade::new(volume, 0.0, crossfade));
     }
 
-    let soundtrack_key = match trigger.event() {
-        PlaySoundtrack::Key(key) => *key,
-        PlaySoundtrack::Disable => return,
+    let Some(key) = key else {
+        return;
+    };
+    manager.last = Some(key);
+    let Some(handle) = playable_handle(asset_server, soundtrack_handles, key) else {
+        return;
     };
     commands.spawn((
         AudioSourceBundle {
-            source: soundtrack_handles[&soundtrack_key].clone_weak(),
+            source: handle.clone_weak(),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Despawn,
+                volume: Volume::ZERO,
                 ..default()
             },
         },
         IsSoundtrack,
+        MusicFade::new(0.0, MUSIC_VOLUME, crossfade),
     ));
 }
 
+/// The handle of `key`, unless it's missing until the asset manifest has loaded
+/// or the track failed to load.
+fn playable_handle<'a>(
+    asset_server: &AssetServer,
+    soundtrack_handles: &'a HandleMap<SoundtrackKey>,
+    key: SoundtrackKey,
+) -> Option<&'a Handle<AudioSource>> {
+    soundtrack_handles
+        .get(&key)
+        .filter(|handle| !matches!(asset_server.load_state(handle.id()), LoadState::Failed(_)))
+}
+
 /// Trigger this event to play or disable the soundtrack.
-/// Playing a new soundtrack will overwrite the previous one.
-/// Soundtracks will loop.
+/// Playing a new soundtrack crossfades out of the previous one.
+/// Playlists pick a new track whenever the current one ends.
 #[derive(Event)]
 pub enum PlaySoundtrack {
     Key(SoundtrackKey),
+    Playlist(PlaylistKey),
     Disable,
 }
 
 /// Marker component for the soundtrack entity so we can find it later.
+/// Tracks that are fading out lose this marker.
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 struct IsSoundtrack;
@@ -0,0 +1,155 @@
+//! Bombs. Using bombs sets one down in front of the player, which blows up after its fuse
+//! burns down. The blast counts as a weapon, so it breaks [`Breakable`] grass and pots.
+//!
+//! [`Breakable`]: super::drops::Breakable
+
+use avian2d::collision::{Collider, CollisionLayers, Sensor};
+use bevy::prelude::*;
+use bevy_spritesheet_animation::component::SpritesheetAnimation;
+
+use super::{
+    animation::{AnimationNames, AnimationSet},
+    assets::{AnimationKey, HandleMap, ImageKey},
+    camera::YSorted,
+    facing::Facing,
+    inventory::ItemUsed,
+    physics::PhysicsLayers,
+};
+use crate::{
+    screen::Screen,
+    tween::{Ease, Tween, TweenCommandsExt, TweenTarget},
+    AppSet,
+};
+
+/// Id of bombs in the item catalog.
+const BOMB_ITEM: &str = "bombs";
+/// How long a bomb's fuse burns, in seconds.
+const FUSE_SECS: f32 = 2.0;
+/// How far in front of the player bombs are set down, in pixels.
+const PLACE_DISTANCE: f32 = 12.0;
+/// Radius of a blast, in pixels.
+const BLAST_RADIUS: f32 = 24.0;
+/// How long a blast lasts, in seconds.
+const BLAST_SECS: f32 = 0.25;
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(place_bombs);
+    app.add_systems(Update, (burn_fuses, clear_blasts).in_set(AppSet::Update));
+}
+
+/// A lit bomb, which blows up when the fuse is done.
+#[derive(Component, Debug)]
+struct Fuse(Timer);
+
+/// A bomb's blast, which only lasts an instant.
+#[derive(Component, Debug)]
+struct Blast(Timer);
+
+fn place_bombs(
+    trigger: Trigger<ItemUsed>,
+    mut commands: Commands,
+    image_handles: Res<HandleMap<ImageKey>>,
+    animation_handles: Res<HandleMap<AnimationKey>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    animation_names: Res<AnimationNames>,
+    user_q: Query<(&GlobalTransform, Option<&Facing>)>,
+) {
+    if trigger.event().item != BOMB_ITEM {
+        return;
+    }
+    let Ok((transform, facing)) = user_q.get(trigger.entity()) else {
+        return;
+    };
+    let (Some(animation_set), Some(animation_id)) = (
+        animation_handles
+            .get(&AnimationKey::Bomb)
+            .and_then(|handle| animation_sets.get(handle)),
+        animation_names.get("bomb"),
+    ) else {
+        error!("bomb animations are not loaded");
+        return;
+    };
+    let texture = animation_set
+        .texture
+        .clone()
+        .unwrap_or_else(|| image_handles[&ImageKey::Bomb].clone_weak());
+    let direction = facing.copied().unwrap_or_default().as_vec2();
+    let position = transform.translation().xy() + direction * PLACE_DISTANCE;
+
+    commands.spawn((
+        Name::new("Bomb"),
+        Fuse(Timer::from_seconds(FUSE_SECS, TimerMode::Once)),
+        TextureAtlas {
+            layout: animation_set.layout.clone(),
+            ..default()
+        },
+        SpriteBundle {
+            texture,
+            transform: Transform::from_translation(position.extend(0.0)),
+            ..default()
+        },
+        SpritesheetAnimation::from_id(animation_id),
+        YSorted::default(),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn burn_fuses(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut bomb_q: Query<(Entity, &Transform, &mut Fuse)>,
+) {
+    for (entity, transform, mut fuse) in &mut bomb_q {
+        fuse.0.tick(time.delta());
+        if !fuse.0.finished() {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+
+        let size = Vec2::splat(2.0 * BLAST_RADIUS);
+        let color = Color::srgb(1.0, 0.85, 0.4);
+        commands
+            .spawn((
+                Name::new("Blast"),
+                Blast(Timer::from_seconds(BLAST_SECS, TimerMode::Once)),
+                Sensor,
+                Collider::circle(BLAST_RADIUS),
+                CollisionLayers::new(PhysicsLayers::Weapon, [PhysicsLayers::World]),
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: *transform,
+                    ..default()
+                },
+                YSorted::default(),
+                StateScoped(Screen::Playing),
+            ))
+            .tween(Tween::new(
+                BLAST_SECS,
+                Ease::QuadOut,
+                TweenTarget::SpriteColor {
+                    from: color,
+                    to: color.with_alpha(0.0),
+                },
+            ))
+            .tween(Tween::new(
+                BLAST_SECS,
+                Ease::BackOut,
+                TweenTarget::Scale {
+                    from: Vec3::splat(0.5),
+                    to: Vec3::ONE,
+                },
+            ));
+    }
+}
+
+fn clear_blasts(time: Res<Time>, mut commands: Commands, mut blast_q: Query<(Entity, &mut Blast)>) {
+    for (entity, mut blast) in &mut blast_q {
+        if blast.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,9 +1,16 @@
 use avian2d::schedule::PhysicsSet;
 use bevy::prelude::*;
 
-use crate::{utils::SmoothNudge, AppSet};
+use crate::{
+    tween::{Ease, Tween, TweenCommandsExt, TweenTarget},
+    utils::SmoothNudge,
+    AppSet,
+};
 
 use super::spawn::player::Player;
+
+/// How long zooming in or out takes, in seconds.
+const ZOOM_SECS: f32 = 0.4;
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Update, (zoom_camera, y_sort_system).in_set(AppSet::Update))
         .add_systems(
@@ -22,20 +29,33 @@ pub struct YSorted {
     offset: f32,
 }
 
+impl YSorted {
+    /// Draw in front of (positive) or behind (negative) other sprites at the same height.
+    pub fn with_priority(mut self, priority: f32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sort as if the sprite were `offset` lower, e.g. because it's drawn raised off the ground.
+    pub fn set_offset(&mut self, offset: f32) {
+        self.offset = offset;
+    }
+}
+
 fn y_sort_system(mut xf_q: Query<(&mut Transform, &GlobalTransform, &YSorted)>) {
     for (mut xf, g_xf, YSorted { priority, offset }) in &mut xf_q {
         xf.translation.z = (-g_xf.translation().y + priority + offset) / 1000.0;
     }
 }
 fn zoom_camera(
-    mut q: Query<(&mut OrthographicProjection, &mut PrimaryCamera), Without<Player>>,
+    mut commands: Commands,
+    mut q: Query<(Entity, &OrthographicProjection, &mut PrimaryCamera), Without<Player>>,
     kb: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
 ) {
-    let dt = time.delta_seconds();
-    let Ok((mut cam, mut cam_zoom)) = q.get_single_mut() else {
+    let Ok((entity, cam, mut cam_zoom)) = q.get_single_mut() else {
         return;
     };
+    let zoom = cam_zoom.1;
     if kb.just_pressed(KeyCode::Equal) {
         cam_zoom.1 *= 0.7;
     }
@@ -46,7 +66,16 @@ fn zoom_camera(
         cam_zoom.2 = !cam_zoom.2;
     }
     cam_zoom.1 = cam_zoom.1.clamp(Vec3::splat(0.01), Vec3::splat(10.0));
-    cam.scale.smooth_nudge(&cam_zoom.1.x, 5.0, dt);
+    if cam_zoom.1 != zoom {
+        commands.entity(entity).tween(Tween::new(
+            ZOOM_SECS,
+            Ease::CubicOut,
+            TweenTarget::CameraScale {
+                from: cam.scale,
+                to: cam_zoom.1.x,
+            },
+        ));
+    }
 }
 fn follow_player(mut q: Query<(&mut Transform, &PrimaryCamera), Without<Player>>, time: Res<Time>) {
     let delta = time.delta_seconds();
@@ -0,0 +1,255 @@
+//! Treasure chests. Interacting with a closed [`Chest`] opens it, picks its contents
+//! from a weighted loot table and shows the player getting them.
+
+use bevy::prelude::*;
+use bevy_spritesheet_animation::component::SpritesheetAnimation;
+use rand::seq::SliceRandom;
+
+use super::{
+    animation::AnimationNames,
+    assets::ItemCatalogHandle,
+    inventory::{ItemCatalog, ItemGranted},
+    item_get::{advance_item_get, ShowItemGet},
+    keys::KeyKind,
+    pause::{GameplayPause, PauseReason},
+    player::Interacted,
+    save::SaveData,
+};
+use crate::{
+    tween::{Ease, Tween, TweenCommandsExt, TweenTarget},
+    AppSet,
+};
+
+/// How long a chest takes to open before its contents are shown, in seconds.
+const OPEN_SECS: f32 = 0.5;
+/// How far a [`ChestLid`] is lifted when its chest opens, in pixels.
+const LID_LIFT: f32 = 6.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Loot>();
+    app.register_type::<Chest>();
+    app.register_type::<ChestLid>();
+    app.observe(open_chests);
+    app.add_systems(
+        Update,
+        (restore_opened_chests, tick_opening_chests)
+            .chain()
+            .in_set(AppSet::Update)
+            .before(advance_item_get),
+    );
+}
+
+/// Something a chest can contain or an enemy can drop.
+#[derive(Reflect, Clone, Debug)]
+pub enum Loot {
+    Rupees(u32),
+    Key {
+        dungeon: String,
+        kind: KeyKind,
+    },
+    /// An item from the [`ItemCatalog`], by id.
+    Item {
+        item: String,
+        count: u32,
+    },
+}
+
+impl Loot {
+    fn description(&self, catalog: Option<&ItemCatalog>) -> String {
+        match self {
+            Self::Rupees(1) => "You got a rupee!".to_string(),
+            Self::Rupees(rupees) => format!("You got {rupees} rupees!"),
+            Self::Key {
+                kind: KeyKind::Small,
+                ..
+            } => "You got a small key!".to_string(),
+            Self::Key {
+                kind: KeyKind::Boss,
+                ..
+            } => "You got the boss key!".to_string(),
+            Self::Item { item, count } => {
+                let name = catalog
+                    .and_then(|catalog| catalog.get(item))
+                    .map_or(item.as_str(), |def| def.name.as_str());
+                match count {
+                    1 => format!("You got {name}!"),
+                    _ => format!("You got {count} {name}!"),
+                }
+            }
+        }
+    }
+}
+
+/// A chest that's opened by interacting with it.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct Chest {
+    /// What the chest may contain, with relative weights. One entry is picked on opening.
+    pub loot: Vec<(Loot, f32)>,
+    /// Chests with an id stay open, even across saves.
+    pub id: Option<String>,
+    /// Played on the chest's [`SpritesheetAnimation`] when it opens, if set.
+    pub open_animation: Option<String>,
+    pub opened: bool,
+}
+
+/// The lid of a [`Chest`] without an open animation, as a child of it.
+/// It's lifted off when the chest opens.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct ChestLid;
+
+/// A chest in the middle of opening, with the loot it's about to give.
+#[derive(Component, Debug)]
+struct Opening {
+    timer: Timer,
+    loot: Option<Loot>,
+}
+
+fn restore_opened_chests(
+    mut commands: Commands,
+    save: Res<SaveData>,
+    animation_names: Res<AnimationNames>,
+    mut chest_q: Query<
+        (
+            &mut Chest,
+            Option<&mut SpritesheetAnimation>,
+            Option<&Children>,
+        ),
+        Added<Chest>,
+    >,
+    lid_q: Query<&Transform, With<ChestLid>>,
+) {
+    for (mut chest, animation, children) in &mut chest_q {
+        if !chest
+            .id
+            .as_ref()
+            .is_some_and(|id| save.collected.contains(id))
+        {
+            continue;
+        }
+        chest.opened = true;
+        play_open_animation(&chest, animation, &animation_names);
+        lift_lids(&mut commands, children, &lid_q, 0.0);
+    }
+}
+
+fn open_chests(
+    trigger: Trigger<Interacted>,
+    mut commands: Commands,
+    mut save: ResMut<SaveData>,
+    mut pause: ResMut<GameplayPause>,
+    animation_names: Res<AnimationNames>,
+    mut chest_q: Query<(
+        &mut Chest,
+        Option<&mut SpritesheetAnimation>,
+        Option<&Children>,
+    )>,
+    lid_q: Query<&Transform, With<ChestLid>>,
+) {
+    let entity = trigger.entity();
+    let Ok((mut chest, animation, children)) = chest_q.get_mut(entity) else {
+        return;
+    };
+    if chest.opened {
+        return;
+    }
+    chest.opened = true;
+    if let Some(id) = &chest.id {
+        save.collected.insert(id.clone());
+    }
+    play_open_animation(&chest, animation, &animation_names);
+    lift_lids(&mut commands, children, &lid_q, OPEN_SECS);
+
+    let loot = chest
+        .loot
+        .choose_weighted(&mut rand::thread_rng(), |(_, weight)| *weight)
+        .ok()
+        .map(|(loot, _)| loot.clone());
+    pause.add(PauseReason::OpeningChest);
+    commands.entity(entity).insert(Opening {
+        timer: Timer::from_seconds(OPEN_SECS, TimerMode::Once),
+        loot,
+    });
+}
+
+fn play_open_animation(
+    chest: &Chest,
+    animation: Option<Mut<SpritesheetAnimation>>,
+    animation_names: &AnimationNames,
+) {
+    let id = chest
+        .open_animation
+        .as_ref()
+        .and_then(|name| animation_names.get(name));
+    if let (Some(id), Some(mut animation)) = (id, animation) {
+        animation.animation_id = id;
+    }
+}
+
+/// Lifts the [`ChestLid`]s among `children` over `secs` seconds.
+fn lift_lids(
+    commands: &mut Commands,
+    children: Option<&Children>,
+    lid_q: &Query<&Transform, With<ChestLid>>,
+    secs: f32,
+) {
+    for &lid in children.into_iter().flatten() {
+        let Ok(transform) = lid_q.get(lid) else {
+            continue;
+        };
+        commands.entity(lid).tween(Tween::new(
+            secs,
+            Ease::BackOut,
+            TweenTarget::Translation {
+                from: transform.translation,
+                to: transform.translation + Vec3::Y * LID_LIFT,
+            },
+        ));
+    }
+}
+
+fn tick_opening_chests(
+    time: Res<Time>,
+    mut commands: Commands,
+    catalog_handle: Res<ItemCatalogHandle>,
+    catalogs: Res<Assets<ItemCatalog>>,
+    mut save: ResMut<SaveData>,
+    mut pause: ResMut<GameplayPause>,
+    mut chest_q: Query<(Entity, &mut Opening)>,
+) {
+    for (entity, mut opening) in &mut chest_q {
+        opening.timer.tick(time.delta());
+        if !opening.timer.finished() {
+            continue;
+        }
+        commands.entity(entity).remove::<Opening>();
+        pause.remove(PauseReason::OpeningChest);
+
+        let Some(loot) = opening.loot.take() else {
+            continue;
+        };
+        grant_loot(&loot, &mut commands, &mut save);
+        commands.trigger(ShowItemGet {
+            text: loot.description(catalogs.get(&catalog_handle.0)),
+        });
+    }
+}
+
+/// Gives the player `loot`.
+pub fn grant_loot(loot: &Loot, commands: &mut Commands, save: &mut SaveData) {
+    match loot {
+        Loot::Rupees(rupees) => save.add_rupees(*rupees),
+        Loot::Key { dungeon, kind } => {
+            let keys = save.keys.entry(dungeon.clone()).or_default();
+            match kind {
+                KeyKind::Small => keys.small += 1,
+                KeyKind::Boss => keys.boss = true,
+            }
+        }
+        Loot::Item { item, count } => commands.trigger(ItemGranted {
+            item: item.clone(),
+            count: *count,
+        }),
+    }
+}
@@ -1,6 +1,14 @@
 use bevy::prelude::*;
 
+/// Size of one grid cell of the world, in pixels.
+pub const TILE_SIZE: f32 = 16.0;
+
 pub const LEFT: &[KeyCode] = &[KeyCode::KeyA, KeyCode::ArrowLeft];
 pub const RIGHT: &[KeyCode] = &[KeyCode::KeyD, KeyCode::ArrowRight];
 pub const UP: &[KeyCode] = &[KeyCode::KeyW, KeyCode::ArrowUp];
 pub const DOWN: &[KeyCode] = &[KeyCode::KeyS, KeyCode::ArrowDown];
+pub const SLIDE: &[KeyCode] = &[KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::KeyK];
+pub const ITEM_SLOT_1: &[KeyCode] = &[KeyCode::KeyJ];
+pub const ITEM_SLOT_2: &[KeyCode] = &[KeyCode::KeyL];
+pub const INVENTORY: &[KeyCode] = &[KeyCode::Tab, KeyCode::KeyI];
+pub const QUEST_LOG: &[KeyCode] = &[KeyCode::KeyQ];
@@ -0,0 +1,488 @@
+//! Cutscenes, authored in `.cutscene.ron` files listed in the asset manifest.
+//! A [`Cutscene`] is a list of [`CutsceneStep`]s that run one after another, like walking
+//! characters around, showing dialogue and panning the camera. Actors are found by their [`Name`].
+//!
+//! The player's input is ignored while a cutscene runs. Escape or Start skips the rest of it,
+//! applying its lasting effects right away: actors are put where they were going,
+//! flags are set and the last music requested starts playing.
+//!
+//! Trigger [`PlayCutscene`] to start one, or have the player walk into a [`CutsceneZone`].
+
+use avian2d::{collision::CollidingEntities, dynamics::rigid_body::LinearVelocity};
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use bevy_spritesheet_animation::{animation::AnimationId, component::SpritesheetAnimation};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    animation::{state::update_animation_states, AnimationNames},
+    assets::{CutsceneKey, DialogueKey, HandleMap, SoundtrackKey},
+    audio::soundtrack::{PlaySoundtrack, PlaylistKey},
+    camera::PrimaryCamera,
+    dialogue::{ActiveDialogue, EndDialogue, StartDialogue},
+    flags::{FlagValue, Requirement},
+    pause::{GameplayPause, PauseReason},
+    physics::MovementAcceleration,
+    player::PlayerDir,
+    save::SaveData,
+    spawn::player::Player,
+};
+use crate::{
+    screen::Screen,
+    tween::{Ease, Tween, TweenCommandsExt, TweenTarget, Tweens},
+    utils::gamepad_just_pressed,
+    AppSet,
+};
+
+/// Actors this close to where they're going have arrived, in pixels.
+const ARRIVE_RADIUS: f32 = 2.0;
+/// Characters slow down within this distance of where they're going, in pixels.
+const SLOW_RADIUS: f32 = 16.0;
+/// How fast actors that can't walk glide by default, in pixels per second.
+const GLIDE_SPEED: f32 = 60.0;
+/// Actors that haven't arrived after this long, say because a wall is in the way,
+/// are put where they were going, in seconds.
+const MOVE_GIVE_UP_SECS: f32 = 8.0;
+/// Keys that skip the running cutscene.
+const SKIP: [KeyCode; 1] = [KeyCode::Escape];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Cutscene>();
+    app.init_asset_loader::<CutsceneLoader>();
+    app.register_type::<CutsceneZone>();
+    app.observe(play_cutscene);
+    app.add_systems(
+        Update,
+        (
+            enter_cutscene_zones,
+            run_cutscene.run_if(resource_exists::<ActiveCutscene>),
+            hold_cutscene_animations
+                .after(update_animation_states)
+                .run_if(resource_exists::<ActiveCutscene>),
+        )
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnExit(Screen::Playing), clear_cutscene);
+}
+
+/// A scripted scene, loaded from a `.cutscene.ron` file.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Cutscene {
+    steps: Vec<CutsceneStep>,
+}
+
+#[derive(Debug, Deserialize)]
+enum CutsceneStep {
+    /// Walks a character to a point, or glides anything else there, and waits until it arrives.
+    /// Characters walk at their own speed unless `speed` is slower.
+    Move {
+        actor: String,
+        to: (f32, f32),
+        #[serde(default)]
+        speed: Option<f32>,
+    },
+    /// Plays an animation on the actor's sprite, or its child's, until the cutscene ends.
+    Animate {
+        actor: String,
+        animation: String,
+    },
+    /// Shows a dialogue and waits until it's over.
+    Dialogue(DialogueKey),
+    /// Moves the camera to a point over `secs` seconds.
+    /// It follows the player again once the cutscene ends.
+    PanCamera {
+        to: (f32, f32),
+        secs: f32,
+        #[serde(default)]
+        ease: Ease,
+    },
+    /// Waits this many seconds.
+    Wait(f32),
+    Soundtrack(SoundtrackKey),
+    Playlist(PlaylistKey),
+    SetFlag(String, FlagValue),
+}
+
+#[derive(Debug, Error)]
+pub enum CutsceneError {
+    #[error("could not read cutscene: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse cutscene: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("cutscene has no steps")]
+    NoSteps,
+}
+
+#[derive(Default)]
+struct CutsceneLoader;
+
+impl AssetLoader for CutsceneLoader {
+    type Asset = Cutscene;
+    type Settings = ();
+    type Error = CutsceneError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let cutscene: Cutscene = ron::de::from_bytes(&bytes)?;
+        if cutscene.steps.is_empty() {
+            return Err(CutsceneError::NoSteps);
+        }
+        Ok(cutscene)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cutscene.ron"]
+    }
+}
+
+/// Triggered to start a cutscene. Ignored while another cutscene is running.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayCutscene(pub CutsceneKey);
+
+/// Plays a cutscene when the player walks in, if `when` holds.
+/// Needs a [`Sensor`](avian2d::collision::Sensor) collider and [`CollidingEntities`].
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct CutsceneZone {
+    pub cutscene: CutsceneKey,
+    pub when: Option<Requirement>,
+    #[reflect(ignore)]
+    inside: bool,
+}
+
+impl CutsceneZone {
+    pub fn new(cutscene: CutsceneKey, when: Option<Requirement>) -> Self {
+        Self {
+            cutscene,
+            when,
+            inside: false,
+        }
+    }
+}
+
+/// The cutscene that's running, if any.
+#[derive(Resource, Debug)]
+pub struct ActiveCutscene {
+    handle: Handle<Cutscene>,
+    step: usize,
+    /// Whether the current step has started, like its dialogue having been shown.
+    started: bool,
+    /// Seconds since the current step started.
+    elapsed: f32,
+    /// Whether the camera followed the player before the cutscene first moved it.
+    camera_followed: Option<bool>,
+    /// Animations the cutscene plays on actors, held until it ends.
+    animations: Vec<(Entity, AnimationId)>,
+}
+
+fn play_cutscene(
+    trigger: Trigger<PlayCutscene>,
+    mut commands: Commands,
+    active: Option<Res<ActiveCutscene>>,
+    handles: Res<HandleMap<CutsceneKey>>,
+    cutscenes: Res<Assets<Cutscene>>,
+    mut pause: ResMut<GameplayPause>,
+) {
+    if active.is_some() {
+        return;
+    }
+    let key = trigger.event().0;
+    let handle = handles[&key].clone_weak();
+    if !cutscenes.contains(&handle) {
+        warn!("cutscene {key:?} is not loaded");
+        return;
+    }
+    commands.insert_resource(ActiveCutscene {
+        handle,
+        step: 0,
+        started: false,
+        elapsed: 0.0,
+        camera_followed: None,
+        animations: vec![],
+    });
+    pause.add(PauseReason::Cutscene);
+}
+
+fn enter_cutscene_zones(
+    mut commands: Commands,
+    save: Res<SaveData>,
+    active: Option<Res<ActiveCutscene>>,
+    mut zone_q: Query<(&mut CutsceneZone, &CollidingEntities)>,
+    player_q: Query<(), With<Player>>,
+) {
+    let mut playing = active.is_some();
+    for (mut zone, colliding) in &mut zone_q {
+        let inside = colliding.iter().any(|&e| player_q.contains(e));
+        let entered = inside && !zone.inside;
+        if entered && !playing && zone.when.as_ref().map_or(true, |when| when.holds(&save)) {
+            commands.trigger(PlayCutscene(zone.cutscene));
+            playing = true;
+        }
+        if zone.inside != inside {
+            zone.inside = inside;
+        }
+    }
+}
+
+type ActorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        Option<&'static mut PlayerDir>,
+        Option<&'static MovementAcceleration>,
+        Option<&'static mut LinearVelocity>,
+    ),
+    Without<PrimaryCamera>,
+>;
+
+fn run_cutscene(
+    mut commands: Commands,
+    time: Res<Time>,
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    cutscenes: Res<Assets<Cutscene>>,
+    animation_names: Res<AnimationNames>,
+    dialogue: Option<Res<ActiveDialogue>>,
+    mut active: ResMut<ActiveCutscene>,
+    mut save: ResMut<SaveData>,
+    mut pause: ResMut<GameplayPause>,
+    name_q: Query<(Entity, &Name)>,
+    mut actor_q: ActorQuery,
+    mut camera_q: Query<(Entity, &Transform, &mut PrimaryCamera)>,
+) {
+    let Some(cutscene) = cutscenes.get(&active.handle) else {
+        end_cutscene(&mut commands, &active, &mut pause, &mut camera_q);
+        return;
+    };
+    if kb.any_just_pressed(SKIP)
+        || gamepad_just_pressed(&gamepads, &buttons, GamepadButtonType::Start)
+    {
+        skip_steps(
+            &mut commands,
+            &cutscene.steps[active.step.min(cutscene.steps.len())..],
+            &mut save,
+            &name_q,
+            &mut actor_q,
+        );
+        if dialogue.is_some() {
+            commands.trigger(EndDialogue);
+        }
+        // Stop any pan that's still going, so the camera can follow the player right away.
+        if let (Some(_), Ok((camera, ..))) = (active.camera_followed, camera_q.get_single()) {
+            commands.entity(camera).remove::<Tweens>();
+        }
+        end_cutscene(&mut commands, &active, &mut pause, &mut camera_q);
+        return;
+    }
+
+    let dt = time.delta_seconds();
+    active.elapsed += dt;
+    while let Some(step) = cutscene.steps.get(active.step) {
+        let first = !active.started;
+        active.started = true;
+        let done = match step {
+            CutsceneStep::Move { actor, to, speed } => {
+                match find_actor(&name_q, actor).map(|entity| actor_q.get_mut(entity)) {
+                    Some(Ok((mut transform, dir, acceleration, velocity))) => {
+                        let to = Vec2::from(*to);
+                        let offset = to - transform.translation.xy();
+                        if offset.length() < ARRIVE_RADIUS || active.elapsed >= MOVE_GIVE_UP_SECS {
+                            place(&mut transform, dir, velocity, to);
+                            true
+                        } else if let (Some(mut dir), Some(acceleration)) = (dir, acceleration) {
+                            let max = speed
+                                .map_or(1.0, |speed| (speed / acceleration.max_speed).min(1.0));
+                            let slow = (offset.length() / SLOW_RADIUS).min(1.0);
+                            dir.0 = offset.normalize() * max * slow;
+                            false
+                        } else {
+                            let distance = speed.unwrap_or(GLIDE_SPEED) * dt;
+                            transform.translation += offset.clamp_length_max(distance).extend(0.0);
+                            false
+                        }
+                    }
+                    _ => {
+                        warn!("cutscene actor \"{actor}\" does not exist");
+                        true
+                    }
+                }
+            }
+            CutsceneStep::Animate { actor, animation } => {
+                match (find_actor(&name_q, actor), animation_names.get(animation)) {
+                    (Some(entity), Some(id)) => {
+                        active.animations.retain(|(held, _)| *held != entity);
+                        active.animations.push((entity, id));
+                    }
+                    (None, _) => warn!("cutscene actor \"{actor}\" does not exist"),
+                    (_, None) => warn!("cutscene animation \"{animation}\" does not exist"),
+                }
+                true
+            }
+            CutsceneStep::Dialogue(key) => {
+                // The dialogue starts once commands are applied, so wait a frame before
+                // checking whether it's over. Dialogues with nothing to say end right away.
+                if first {
+                    commands.trigger(StartDialogue(*key));
+                    false
+                } else {
+                    dialogue.is_none()
+                }
+            }
+            CutsceneStep::PanCamera { to, secs, ease } => match camera_q.get_single_mut() {
+                Ok((camera, cam_tf, mut cam)) => {
+                    if first {
+                        if active.camera_followed.is_none() {
+                            active.camera_followed = Some(cam.2);
+                        }
+                        cam.2 = false;
+                        let to = Vec2::from(*to).extend(cam_tf.translation.z);
+                        commands.entity(camera).tween(Tween::new(
+                            *secs,
+                            *ease,
+                            TweenTarget::Translation {
+                                from: cam_tf.translation,
+                                to,
+                            },
+                        ));
+                    }
+                    active.elapsed >= *secs
+                }
+                Err(_) => true,
+            },
+            CutsceneStep::Wait(secs) => active.elapsed >= *secs,
+            CutsceneStep::Soundtrack(key) => {
+                commands.trigger(PlaySoundtrack::Key(*key));
+                true
+            }
+            CutsceneStep::Playlist(key) => {
+                commands.trigger(PlaySoundtrack::Playlist(*key));
+                true
+            }
+            CutsceneStep::SetFlag(flag, value) => {
+                save.set_flag(flag.clone(), *value);
+                true
+            }
+        };
+        if !done {
+            return;
+        }
+        active.step += 1;
+        active.started = false;
+        active.elapsed = 0.0;
+    }
+    end_cutscene(&mut commands, &active, &mut pause, &mut camera_q);
+}
+
+fn find_actor(name_q: &Query<(Entity, &Name)>, name: &str) -> Option<Entity> {
+    name_q
+        .iter()
+        .find(|(_, actor)| actor.as_str() == name)
+        .map(|(entity, _)| entity)
+}
+
+/// Puts an actor down at `to`, stopping it.
+fn place(
+    transform: &mut Transform,
+    dir: Option<Mut<PlayerDir>>,
+    velocity: Option<Mut<LinearVelocity>>,
+    to: Vec2,
+) {
+    transform.translation = to.extend(transform.translation.z);
+    if let Some(mut dir) = dir {
+        dir.0 = Vec2::ZERO;
+    }
+    if let Some(mut velocity) = velocity {
+        velocity.0 = Vec2::ZERO;
+    }
+}
+
+/// Applies what's left of a skipped cutscene that should last after it.
+/// Dialogue is skipped along with any flags it would have set.
+fn skip_steps(
+    commands: &mut Commands,
+    steps: &[CutsceneStep],
+    save: &mut SaveData,
+    name_q: &Query<(Entity, &Name)>,
+    actor_q: &mut ActorQuery,
+) {
+    let mut music = None;
+    for step in steps {
+        match step {
+            CutsceneStep::Move { actor, to, .. } => {
+                if let Some(Ok((mut transform, dir, _, velocity))) =
+                    find_actor(name_q, actor).map(|entity| actor_q.get_mut(entity))
+                {
+                    place(&mut transform, dir, velocity, Vec2::from(*to));
+                }
+            }
+            CutsceneStep::Soundtrack(key) => music = Some(PlaySoundtrack::Key(*key)),
+            CutsceneStep::Playlist(key) => music = Some(PlaySoundtrack::Playlist(*key)),
+            CutsceneStep::SetFlag(flag, value) => save.set_flag(flag.clone(), *value),
+            CutsceneStep::Animate { .. }
+            | CutsceneStep::Dialogue(_)
+            | CutsceneStep::PanCamera { .. }
+            | CutsceneStep::Wait(_) => {}
+        }
+    }
+    if let Some(music) = music {
+        commands.trigger(music);
+    }
+}
+
+fn end_cutscene(
+    commands: &mut Commands,
+    active: &ActiveCutscene,
+    pause: &mut GameplayPause,
+    camera_q: &mut Query<(Entity, &Transform, &mut PrimaryCamera)>,
+) {
+    commands.remove_resource::<ActiveCutscene>();
+    pause.remove(PauseReason::Cutscene);
+    if let (Some(followed), Ok((.., mut cam))) = (active.camera_followed, camera_q.get_single_mut())
+    {
+        cam.2 = followed;
+    }
+}
+
+/// Keeps the animations played by the cutscene on, over whatever the actors' state machines pick.
+fn hold_cutscene_animations(
+    active: Res<ActiveCutscene>,
+    children_q: Query<&Children>,
+    mut sprite_q: Query<&mut SpritesheetAnimation>,
+) {
+    for &(actor, id) in &active.animations {
+        let children = children_q
+            .get(actor)
+            .into_iter()
+            .flat_map(|children| children.iter().copied());
+        let mut iter = sprite_q.iter_many_mut(std::iter::once(actor).chain(children));
+        while let Some(mut animation) = iter.fetch_next() {
+            if animation.animation_id != id {
+                animation.animation_id = id;
+            }
+        }
+    }
+}
+
+fn clear_cutscene(
+    mut commands: Commands,
+    active: Option<Res<ActiveCutscene>>,
+    mut pause: ResMut<GameplayPause>,
+    mut camera_q: Query<(Entity, &Transform, &mut PrimaryCamera)>,
+) {
+    if let Some(active) = active {
+        end_cutscene(&mut commands, &active, &mut pause, &mut camera_q);
+    }
+}
@@ -0,0 +1,444 @@
+//! Conversations, authored in `.dialogue.ron` files listed in the asset manifest.
+//! A [`Dialogue`] is a graph of nodes, each some lines said by a speaker, followed by
+//! choices for the player or the next node. Lines can show variables, like `{rupees}`,
+//! and nodes, choices and branches can depend on game flags through [`Requirement`]s.
+//!
+//! Interacting with a [`Talker`] starts their dialogue. It can also be started directly
+//! by triggering [`StartDialogue`], and ended early by triggering [`EndDialogue`].
+
+mod text_box;
+
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    assets::{DialogueKey, HandleMap, ImageKey},
+    constants::{DOWN, UP},
+    flags::{FlagValue, Requirement},
+    pause::{GameplayPause, PauseReason},
+    player::{interact_system, Interacted},
+    save::SaveData,
+};
+use crate::{screen::Screen, utils::gamepad_just_pressed, AppSet};
+
+/// How fast lines are typed out, in characters per second.
+const CHARS_PER_SEC: f32 = 40.0;
+/// Keys that finish typing out a line, and then go on to the next one.
+const CONFIRM: [KeyCode; 3] = [KeyCode::KeyE, KeyCode::Space, KeyCode::Enter];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Dialogue>();
+    app.init_asset_loader::<DialogueLoader>();
+    app.register_type::<Talker>();
+    app.observe(talk_to_talkers);
+    app.observe(start_dialogue);
+    app.observe(end_dialogue_early);
+    app.add_systems(
+        Update,
+        advance_dialogue
+            .run_if(resource_exists::<ActiveDialogue>)
+            // Ending the dialogue unpauses gameplay right away, and the key that
+            // ended it shouldn't also start talking to the talker again.
+            .after(interact_system)
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnExit(Screen::Playing), clear_dialogue);
+    app.add_plugins(text_box::plugin);
+}
+
+/// A conversation graph, loaded from a `.dialogue.ron` file.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Dialogue {
+    /// Where the dialogue starts: the first branch whose requirement holds.
+    start: Vec<Branch>,
+    nodes: HashMap<String, DialogueNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DialogueNode {
+    /// Name shown above the lines.
+    #[serde(default)]
+    speaker: Option<String>,
+    #[serde(default)]
+    portrait: Option<ImageKey>,
+    /// Shown one after another. `{name}` is replaced with the variable `name`.
+    lines: Vec<String>,
+    /// Offered after the last line. Choices whose requirement doesn't hold are left out.
+    #[serde(default)]
+    choices: Vec<Choice>,
+    /// Where to go after the last line if there are no choices:
+    /// the first branch whose requirement holds. The dialogue ends if there is none.
+    #[serde(default)]
+    next: Vec<Branch>,
+    /// Flags set when the node is reached.
+    #[serde(default)]
+    set: Vec<(String, FlagValue)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    text: String,
+    /// The node it leads to. The dialogue ends if there is none.
+    #[serde(default)]
+    next: Option<String>,
+    #[serde(default)]
+    when: Option<Requirement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    node: String,
+    #[serde(default)]
+    when: Option<Requirement>,
+}
+
+impl Branch {
+    fn pick<'a>(branches: &'a [Branch], save: &SaveData) -> Option<&'a str> {
+        branches
+            .iter()
+            .find(|branch| branch.when.as_ref().map_or(true, |when| when.holds(save)))
+            .map(|branch| branch.node.as_str())
+    }
+}
+
+impl Dialogue {
+    /// Checks that every node has lines and every node it leads to exists.
+    fn validate(&self) -> Result<(), DialogueError> {
+        let links = self.start.iter().map(|branch| ("start", &branch.node));
+        let node_links = self.nodes.iter().flat_map(|(id, node)| {
+            let choices = node
+                .choices
+                .iter()
+                .filter_map(|choice| choice.next.as_ref());
+            let next = node.next.iter().map(|branch| &branch.node);
+            choices.chain(next).map(move |to| (id.as_str(), to))
+        });
+        for (from, to) in links.chain(node_links) {
+            if !self.nodes.contains_key(to) {
+                return Err(DialogueError::UnknownNode {
+                    from: from.to_string(),
+                    to: to.clone(),
+                });
+            }
+        }
+        match self.nodes.iter().find(|(_, node)| node.lines.is_empty()) {
+            Some((id, _)) => Err(DialogueError::NoLines(id.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DialogueError {
+    #[error("could not read dialogue: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse dialogue: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("dialogue node \"{from}\" leads to \"{to}\", which does not exist")]
+    UnknownNode { from: String, to: String },
+    #[error("dialogue node \"{0}\" has no lines")]
+    NoLines(String),
+}
+
+#[derive(Default)]
+struct DialogueLoader;
+
+impl AssetLoader for DialogueLoader {
+    type Asset = Dialogue;
+    type Settings = ();
+    type Error = DialogueError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let dialogue: Dialogue = ron::de::from_bytes(&bytes)?;
+        dialogue.validate()?;
+        Ok(dialogue)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialogue.ron"]
+    }
+}
+
+/// Starts this dialogue when the player interacts with the entity.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Talker(pub DialogueKey);
+
+/// Triggered to start a dialogue, on the entity that's talking or globally.
+/// Ignored while another dialogue is running.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct StartDialogue(pub DialogueKey);
+
+/// Triggered when a dialogue is over, on the entity that was talking or globally.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DialogueEnded;
+
+/// Triggered to end the running dialogue early, like when a cutscene is skipped.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct EndDialogue;
+
+/// The dialogue that's running, if any.
+#[derive(Resource, Debug)]
+pub struct ActiveDialogue {
+    /// The entity that's talking, if the dialogue was started on one.
+    talker: Option<Entity>,
+    handle: Handle<Dialogue>,
+    node: String,
+    line: usize,
+    /// The current line, with variables filled in.
+    text: String,
+    /// How many characters of the line are typed out.
+    shown: f32,
+    speaker: Option<String>,
+    portrait: Option<ImageKey>,
+    /// Text and target node of the choices offered after the last line.
+    choices: Vec<(String, Option<String>)>,
+    selected: usize,
+}
+
+impl ActiveDialogue {
+    /// The entity that's talking, if the dialogue was started on one.
+    pub fn talker(&self) -> Option<Entity> {
+        self.talker
+    }
+
+    fn length(&self) -> f32 {
+        self.text.chars().count() as f32
+    }
+
+    fn is_typing(&self) -> bool {
+        self.shown < self.length()
+    }
+
+    /// The part of the line that's typed out.
+    fn shown_text(&self) -> &str {
+        let end = self
+            .text
+            .char_indices()
+            .nth(self.shown as usize)
+            .map_or(self.text.len(), |(index, _)| index);
+        &self.text[..end]
+    }
+
+    fn is_last_line(&self, dialogue: &Dialogue) -> bool {
+        dialogue
+            .nodes
+            .get(&self.node)
+            .map_or(true, |node| self.line + 1 >= node.lines.len())
+    }
+
+    /// Whether the choices are on screen.
+    fn is_choosing(&self, dialogue: &Dialogue) -> bool {
+        !self.choices.is_empty() && !self.is_typing() && self.is_last_line(dialogue)
+    }
+
+    /// Goes to a node, setting its flags.
+    fn enter(&mut self, dialogue: &Dialogue, id: &str, save: &mut SaveData) {
+        let Some(node) = dialogue.nodes.get(id) else {
+            return;
+        };
+        for (flag, value) in &node.set {
+            save.set_flag(flag.clone(), *value);
+        }
+        self.node = id.to_string();
+        self.speaker.clone_from(&node.speaker);
+        self.portrait = node.portrait;
+        self.choices = node
+            .choices
+            .iter()
+            .filter(|choice| choice.when.as_ref().map_or(true, |when| when.holds(save)))
+            .map(|choice| (interpolate(&choice.text, save), choice.next.clone()))
+            .collect();
+        self.selected = 0;
+        self.show_line(dialogue, 0, save);
+    }
+
+    fn show_line(&mut self, dialogue: &Dialogue, line: usize, save: &SaveData) {
+        let Some(text) = dialogue
+            .nodes
+            .get(&self.node)
+            .and_then(|node| node.lines.get(line))
+        else {
+            return;
+        };
+        self.line = line;
+        self.text = interpolate(text, save);
+        self.shown = 0.0;
+    }
+}
+
+/// Replaces every `{name}` in `text` with the value of the variable `name`:
+/// `rupees`, `item:<id>` for how many of an item the player holds, or any game flag.
+/// Unknown variables are left as they are.
+fn interpolate(text: &str, save: &SaveData) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let variable = &rest[start + 1..start + length];
+        let value = if variable == "rupees" {
+            Some(save.rupees.to_string())
+        } else if let Some(item) = variable.strip_prefix("item:") {
+            Some(save.inventory.count(item).to_string())
+        } else {
+            save.flag(variable).map(|value| value.to_string())
+        };
+        match value {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..=start + length]),
+        }
+        rest = &rest[start + length + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn talk_to_talkers(trigger: Trigger<Interacted>, mut commands: Commands, talker_q: Query<&Talker>) {
+    let entity = trigger.entity();
+    if let Ok(talker) = talker_q.get(entity) {
+        commands.trigger_targets(StartDialogue(talker.0), entity);
+    }
+}
+
+fn start_dialogue(
+    trigger: Trigger<StartDialogue>,
+    mut commands: Commands,
+    active: Option<Res<ActiveDialogue>>,
+    handles: Res<HandleMap<DialogueKey>>,
+    dialogues: Res<Assets<Dialogue>>,
+    mut save: ResMut<SaveData>,
+    mut pause: ResMut<GameplayPause>,
+) {
+    if active.is_some() {
+        return;
+    }
+    let key = trigger.event().0;
+    let handle = handles[&key].clone_weak();
+    let Some(dialogue) = dialogues.get(&handle) else {
+        warn!("dialogue {key:?} is not loaded");
+        return;
+    };
+    let Some(start) = Branch::pick(&dialogue.start, &save) else {
+        return;
+    };
+    let talker = Some(trigger.entity()).filter(|&entity| entity != Entity::PLACEHOLDER);
+    let mut active = ActiveDialogue {
+        talker,
+        handle,
+        node: String::new(),
+        line: 0,
+        text: String::new(),
+        shown: 0.0,
+        speaker: None,
+        portrait: None,
+        choices: vec![],
+        selected: 0,
+    };
+    active.enter(dialogue, start, &mut save);
+    commands.insert_resource(active);
+    pause.add(PauseReason::Dialogue);
+}
+
+fn advance_dialogue(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    dialogues: Res<Assets<Dialogue>>,
+    mut active: ResMut<ActiveDialogue>,
+    mut save: ResMut<SaveData>,
+    mut pause: ResMut<GameplayPause>,
+) {
+    let Some(dialogue) = dialogues.get(&active.handle) else {
+        end_dialogue(&mut commands, &active, &mut pause);
+        return;
+    };
+    if active.is_typing() {
+        active.shown = (active.shown + CHARS_PER_SEC * time.delta_seconds()).min(active.length());
+    }
+    // The input that started the dialogue shouldn't also advance it.
+    if active.is_added() {
+        return;
+    }
+
+    let pressed = |keys: &[KeyCode], button| {
+        kb.any_just_pressed(keys.iter().copied())
+            || gamepad_just_pressed(&gamepads, &buttons, button)
+    };
+    if active.is_choosing(dialogue) {
+        if pressed(UP, GamepadButtonType::DPadUp) && active.selected > 0 {
+            active.selected -= 1;
+        } else if pressed(DOWN, GamepadButtonType::DPadDown)
+            && active.selected + 1 < active.choices.len()
+        {
+            active.selected += 1;
+        }
+    }
+    if !pressed(&CONFIRM, GamepadButtonType::South) {
+        return;
+    }
+    if active.is_typing() {
+        active.shown = active.length();
+        return;
+    }
+    if !active.is_last_line(dialogue) {
+        let line = active.line + 1;
+        active.show_line(dialogue, line, &save);
+        return;
+    }
+
+    let next = if active.choices.is_empty() {
+        dialogue
+            .nodes
+            .get(&active.node)
+            .and_then(|node| Branch::pick(&node.next, &save))
+            .map(str::to_string)
+    } else {
+        active.choices[active.selected].1.clone()
+    };
+    match next {
+        Some(next) => active.enter(dialogue, &next, &mut save),
+        None => end_dialogue(&mut commands, &active, &mut pause),
+    }
+}
+
+fn end_dialogue(commands: &mut Commands, active: &ActiveDialogue, pause: &mut GameplayPause) {
+    commands.remove_resource::<ActiveDialogue>();
+    pause.remove(PauseReason::Dialogue);
+    match active.talker {
+        Some(talker) => commands.trigger_targets(DialogueEnded, talker),
+        None => commands.trigger(DialogueEnded),
+    }
+}
+
+fn end_dialogue_early(
+    _trigger: Trigger<EndDialogue>,
+    mut commands: Commands,
+    active: Option<Res<ActiveDialogue>>,
+    mut pause: ResMut<GameplayPause>,
+) {
+    if let Some(active) = active {
+        end_dialogue(&mut commands, &active, &mut pause);
+    }
+}
+
+fn clear_dialogue(mut commands: Commands) {
+    commands.remove_resource::<ActiveDialogue>();
+}
@@ -0,0 +1,188 @@
+//! The text box showing the [`ActiveDialogue`]: the speaker's portrait and name,
+//! the line being typed out and, after the last line, the choices.
+
+use bevy::prelude::*;
+
+use super::{ActiveDialogue, Dialogue};
+use crate::{
+    game::assets::{HandleMap, ImageKey},
+    screen::Screen,
+    ui::prelude::*,
+    AppSet,
+};
+
+/// Width and height of the speaker's portrait, in pixels.
+const PORTRAIT_SIZE: f32 = 64.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            spawn_text_box.run_if(resource_added::<ActiveDialogue>),
+            despawn_text_box.run_if(resource_removed::<ActiveDialogue>()),
+            update_text_box.run_if(resource_exists_and_changed::<ActiveDialogue>),
+        )
+            .chain()
+            .after(super::advance_dialogue)
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+#[derive(Component, Debug)]
+struct DialogueTextBox;
+
+#[derive(Component, Debug)]
+struct DialoguePortrait;
+
+#[derive(Component, Debug)]
+struct DialogueSpeaker;
+
+#[derive(Component, Debug)]
+struct DialogueLine;
+
+#[derive(Component, Debug)]
+struct DialogueChoices;
+
+fn spawn_text_box(mut commands: Commands) {
+    let text = |size, color| {
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+    commands
+        .spawn((
+            Name::new("Dialogue Text Box"),
+            DialogueTextBox,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(40.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(700.0),
+                        min_height: Val::Px(PORTRAIT_SIZE),
+                        padding: UiRect::all(Val::Px(16.0)),
+                        column_gap: Val::Px(16.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(ui_palette::NODE_BACKGROUND),
+                    ..default()
+                })
+                .with_children(|children| {
+                    children.spawn((
+                        Name::new("Portrait"),
+                        DialoguePortrait,
+                        ImageBundle {
+                            style: Style {
+                                width: Val::Px(PORTRAIT_SIZE),
+                                height: Val::Px(PORTRAIT_SIZE),
+                                flex_shrink: 0.0,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ));
+                    children
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                flex_grow: 1.0,
+                                row_gap: Val::Px(8.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|children| {
+                            children.spawn((
+                                Name::new("Speaker"),
+                                DialogueSpeaker,
+                                text(28.0, ui_palette::HEADER_TEXT),
+                            ));
+                            children.spawn((
+                                Name::new("Line"),
+                                DialogueLine,
+                                text(24.0, ui_palette::BUTTON_TEXT),
+                            ));
+                            children.spawn((
+                                Name::new("Choices"),
+                                DialogueChoices,
+                                text(24.0, ui_palette::LABEL_TEXT),
+                            ));
+                        });
+                });
+        });
+}
+
+fn despawn_text_box(mut commands: Commands, text_box_q: Query<Entity, With<DialogueTextBox>>) {
+    for entity in &text_box_q {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_text_box(
+    active: Res<ActiveDialogue>,
+    dialogues: Res<Assets<Dialogue>>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    mut portrait_q: Query<(&mut UiImage, &mut Style), With<DialoguePortrait>>,
+    mut speaker_q: Query<
+        &mut Text,
+        (
+            With<DialogueSpeaker>,
+            Without<DialogueLine>,
+            Without<DialogueChoices>,
+        ),
+    >,
+    mut line_q: Query<&mut Text, (With<DialogueLine>, Without<DialogueChoices>)>,
+    mut choices_q: Query<&mut Text, With<DialogueChoices>>,
+) {
+    for (mut image, mut style) in &mut portrait_q {
+        match active.portrait {
+            Some(portrait) => {
+                image.texture = image_handles[&portrait].clone_weak();
+                style.display = Display::Flex;
+            }
+            None => style.display = Display::None,
+        }
+    }
+    for mut text in &mut speaker_q {
+        text.sections[0].value = active.speaker.clone().unwrap_or_default();
+    }
+    for mut text in &mut line_q {
+        text.sections[0].value = active.shown_text().to_string();
+    }
+    let choosing = dialogues
+        .get(&active.handle)
+        .is_some_and(|dialogue| active.is_choosing(dialogue));
+    for mut text in &mut choices_q {
+        text.sections[0].value = if choosing {
+            active
+                .choices
+                .iter()
+                .enumerate()
+                .map(|(index, (choice, _))| {
+                    let cursor = if index == active.selected { ">" } else { " " };
+                    format!("{cursor} {choice}")
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            String::new()
+        };
+    }
+}
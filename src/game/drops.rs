@@ -0,0 +1,198 @@
+//! Things left behind by defeated enemies and broken grass and pots, rolled from a [`DropTable`].
+//! Dropped [`Pickup`]s fly toward the player once they're close and are collected on touch.
+
+use avian2d::collision::{CollidingEntities, CollisionLayers};
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{
+    assets::SfxKey,
+    audio::sfx::PlaySfx,
+    camera::YSorted,
+    chest::{grant_loot, Loot},
+    physics::PhysicsLayers,
+    save::SaveData,
+    spawn::player::Player,
+};
+use crate::{screen::Screen, AppSet};
+
+/// How long a new pickup stays put before it can be collected, in seconds.
+const PICKUP_DELAY_SECS: f32 = 0.4;
+/// Pickups closer than this to the player fly toward them, in pixels.
+const MAGNET_RADIUS: f32 = 48.0;
+/// How fast pickups fly toward the player, in pixels per second.
+const MAGNET_SPEED: f32 = 220.0;
+/// Pickups closer than this to the player are collected, in pixels.
+const COLLECT_RADIUS: f32 = 8.0;
+/// How far from the dropping entity pickups land, at most, in pixels.
+const SCATTER: f32 = 6.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DropTable>();
+    app.register_type::<Breakable>();
+    app.register_type::<Pickup>();
+    app.observe(drop_loot);
+    app.add_systems(
+        Update,
+        (break_on_hit, attract_pickups, collect_pickups)
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// What an entity may drop when it's [`Defeated`].
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct DropTable {
+    /// What may drop, with relative weights.
+    pub drops: Vec<(Loot, f32)>,
+    /// Relative weight of dropping nothing.
+    pub nothing: f32,
+}
+
+impl DropTable {
+    fn roll(&self, rng: &mut impl Rng) -> Option<&Loot> {
+        let total = self.nothing + self.drops.iter().map(|(_, weight)| weight).sum::<f32>();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0.0..total);
+        for (drop, weight) in &self.drops {
+            if roll < *weight {
+                return Some(drop);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+/// Triggered on an enemy, pot or patch of grass when it's destroyed.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Defeated;
+
+/// Grass, pots and the like, destroyed when hit by a weapon.
+/// Needs a collider that detects [`PhysicsLayers::Weapon`].
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Breakable;
+
+/// A dropped thing waiting to be collected.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct Pickup {
+    pub loot: Loot,
+    #[reflect(ignore)]
+    age: f32,
+}
+
+impl Pickup {
+    pub fn new(loot: Loot) -> Self {
+        Self { loot, age: 0.0 }
+    }
+}
+
+fn break_on_hit(
+    mut commands: Commands,
+    breakable_q: Query<(Entity, &CollidingEntities), With<Breakable>>,
+    layers_q: Query<&CollisionLayers>,
+) {
+    for (entity, colliding) in &breakable_q {
+        let hit = colliding.iter().any(|&other| {
+            layers_q
+                .get(other)
+                .is_ok_and(|layers| layers.memberships.has_all(PhysicsLayers::Weapon))
+        });
+        if hit {
+            commands.trigger_targets(Defeated, entity);
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn drop_loot(
+    trigger: Trigger<Defeated>,
+    mut commands: Commands,
+    table_q: Query<(&DropTable, &GlobalTransform)>,
+) {
+    let Ok((table, transform)) = table_q.get(trigger.entity()) else {
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    let Some(loot) = table.roll(&mut rng) else {
+        return;
+    };
+    let offset = Vec2::new(
+        rng.gen_range(-SCATTER..SCATTER),
+        rng.gen_range(-SCATTER..SCATTER),
+    );
+    let (color, size) = match loot {
+        Loot::Rupees(1) => (Color::srgb(0.3, 0.8, 0.3), Vec2::new(4.0, 7.0)),
+        Loot::Rupees(2..=5) => (Color::srgb(0.3, 0.5, 0.9), Vec2::new(4.0, 7.0)),
+        Loot::Rupees(_) => (Color::srgb(0.9, 0.3, 0.3), Vec2::new(4.0, 7.0)),
+        Loot::Key { .. } => (Color::srgb(0.95, 0.8, 0.3), Vec2::new(4.0, 8.0)),
+        Loot::Item { .. } => (Color::WHITE, Vec2::splat(6.0)),
+    };
+    commands.spawn((
+        Name::new("Pickup"),
+        Pickup::new(loot.clone()),
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(
+                (transform.translation().xy() + offset).extend(0.0),
+            ),
+            ..default()
+        },
+        YSorted::default(),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn attract_pickups(
+    time: Res<Time>,
+    player_q: Query<&Transform, With<Player>>,
+    mut pickup_q: Query<(&mut Transform, &mut Pickup), Without<Player>>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    let target = player.translation.xy();
+    for (mut transform, mut pickup) in &mut pickup_q {
+        pickup.age += time.delta_seconds();
+        if pickup.age < PICKUP_DELAY_SECS {
+            continue;
+        }
+        let position = transform.translation.xy();
+        let distance = position.distance(target);
+        if distance < MAGNET_RADIUS {
+            let step = (MAGNET_SPEED * time.delta_seconds()).min(distance);
+            let position = position + (target - position).normalize_or_zero() * step;
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut save: ResMut<SaveData>,
+    player_q: Query<(Entity, &Transform), With<Player>>,
+    pickup_q: Query<(Entity, &Transform, &Pickup), Without<Player>>,
+) {
+    let Ok((player, player_transform)) = player_q.get_single() else {
+        return;
+    };
+    let target = player_transform.translation.xy();
+    for (entity, transform, pickup) in &pickup_q {
+        let close = transform.translation.xy().distance(target) < COLLECT_RADIUS;
+        if pickup.age < PICKUP_DELAY_SECS || !close {
+            continue;
+        }
+        grant_loot(&pickup.loot, &mut commands, &mut save);
+        commands.trigger_targets(PlaySfx::Key(SfxKey::Pickup), player);
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,87 @@
+//! The direction characters are facing.
+
+use avian2d::dynamics::rigid_body::LinearVelocity;
+use bevy::prelude::*;
+
+use super::player::PlayerDir;
+use crate::AppSet;
+
+/// Characters slower than this keep facing the way they were.
+const TURN_SPEED: f32 = 2.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Facing>();
+    app.add_systems(Update, update_facing.in_set(AppSet::Update));
+}
+
+/// The direction a character faces. It follows the character's input,
+/// or its velocity if it has none, and stays put while standing still.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub enum Facing {
+    Up,
+    #[default]
+    Down,
+    Left,
+    Right,
+}
+
+impl Facing {
+    pub fn as_vec2(self) -> Vec2 {
+        match self {
+            Self::Up => Vec2::Y,
+            Self::Down => Vec2::NEG_Y,
+            Self::Left => Vec2::NEG_X,
+            Self::Right => Vec2::X,
+        }
+    }
+
+    /// The facing closest to `direction`, or `None` for a zero vector.
+    pub fn from_direction(direction: Vec2) -> Option<Self> {
+        if direction == Vec2::ZERO {
+            None
+        } else if direction.x.abs() > direction.y.abs() {
+            Some(if direction.x < 0.0 {
+                Self::Left
+            } else {
+                Self::Right
+            })
+        } else {
+            Some(if direction.y < 0.0 {
+                Self::Down
+            } else {
+                Self::Up
+            })
+        }
+    }
+
+    /// Turn towards `direction`. Moving diagonally keeps the current facing
+    /// if it's one of the two directions involved, so it doesn't flicker.
+    fn turn_towards(&mut self, direction: Vec2) {
+        let Some(facing) = Self::from_direction(direction) else {
+            return;
+        };
+        let keeps_facing =
+            self.as_vec2().dot(direction.normalize()) >= std::f32::consts::FRAC_1_SQRT_2 - 0.01;
+        if facing != *self && !keeps_facing {
+            *self = facing;
+        }
+    }
+}
+
+pub(super) fn update_facing(
+    mut facing_q: Query<(&mut Facing, Option<&PlayerDir>, Option<&LinearVelocity>)>,
+) {
+    for (mut facing, input, velocity) in &mut facing_q {
+        let direction = match (input, velocity) {
+            (Some(input), _) if input.0 != Vec2::ZERO => input.0,
+            (_, Some(velocity)) if velocity.length() > TURN_SPEED => velocity.0,
+            _ => continue,
+        };
+        let mut next = *facing;
+        next.turn_towards(direction);
+        if next != *facing {
+            *facing = next;
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Game flags: named values recording what happened in the world, like who the player
+//! has talked to. Flags are kept in the [`SaveData`]. A [`Requirement`] checks them,
+//! along with quests and the player's items and rupees. Dialogue, quests and signal
+//! emitters can all depend on requirements.
+
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::save::SaveData;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FlagValue>();
+    app.register_type::<Requirement>();
+}
+
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlagValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl FlagValue {
+    /// The value as a number, with `true` as 1.
+    pub fn as_int(self) -> i32 {
+        match self {
+            Self::Bool(value) => value.into(),
+            Self::Int(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for FlagValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => value.fmt(f),
+            Self::Int(value) => value.fmt(f),
+        }
+    }
+}
+
+/// Something about the game's state that has to be true, like a flag being set.
+/// Unset flags count as `false` and 0.
+#[derive(Reflect, Clone, Debug, Deserialize)]
+pub enum Requirement {
+    /// The flag is `true` or not 0.
+    Flag(String),
+    /// The flag is `false`, 0 or unset.
+    NotFlag(String),
+    FlagAtLeast(String, i32),
+    FlagBelow(String, i32),
+    FlagEquals(String, i32),
+    /// The player holds at least this many of an item.
+    HasItem(String, u32),
+    /// The player has at least this many rupees.
+    Rupees(u32),
+    /// The quest with this id has started, and may be complete.
+    QuestStarted(String),
+    QuestComplete(String),
+    All(Vec<Requirement>),
+    Any(Vec<Requirement>),
+}
+
+impl Requirement {
+    pub fn holds(&self, save: &SaveData) -> bool {
+        let flag = |name: &String| save.flag(name).map_or(0, FlagValue::as_int);
+        match self {
+            Self::Flag(name) => flag(name) != 0,
+            Self::NotFlag(name) => flag(name) == 0,
+            Self::FlagAtLeast(name, value) => flag(name) >= *value,
+            Self::FlagBelow(name, value) => flag(name) < *value,
+            Self::FlagEquals(name, value) => flag(name) == *value,
+            Self::HasItem(item, count) => save.inventory.count(item) >= *count,
+            Self::Rupees(rupees) => save.rupees >= *rupees,
+            Self::QuestStarted(quest) => save.quests.contains_key(quest),
+            Self::QuestComplete(quest) => save.quests.get(quest).is_some_and(|q| q.complete),
+            Self::All(requirements) => requirements.iter().all(|r| r.holds(save)),
+            Self::Any(requirements) => requirements.iter().any(|r| r.holds(save)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Ground materials and surfaces, and what actors are currently standing on.
+
+use avian2d::collision::CollidingEntities;
+use bevy::prelude::*;
+
+use super::height::Height;
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GroundMaterial>();
+    app.register_type::<Footing>();
+    app.register_type::<Surface>();
+    app.register_type::<OnSurface>();
+    app.add_systems(
+        Update,
+        (update_footing, update_surfaces).in_set(AppSet::Update),
+    );
+}
+
+/// The material of a patch of ground.
+/// Add it to a [`Sensor`](avian2d::collision::Sensor) on
+/// [`PhysicsLayers::Zone`](super::physics::PhysicsLayers::Zone)
+/// together with [`CollidingEntities`] to mark the area it covers.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub enum GroundMaterial {
+    #[default]
+    Grass,
+    Stone,
+    Wood,
+    Water,
+    Sand,
+}
+
+/// The ground material an actor is standing on.
+/// Falls back to [`GroundMaterial::default`] outside of any material zone.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug, Deref)]
+#[reflect(Component)]
+pub struct Footing(pub GroundMaterial);
+
+fn update_footing(
+    mut actor_q: Query<(Entity, &mut Footing)>,
+    ground_q: Query<(&GroundMaterial, &CollidingEntities)>,
+) {
+    for (entity, mut footing) in &mut actor_q {
+        let material = ground_q
+            .iter()
+            .find(|(_, colliding)| colliding.contains(&entity))
+            .map(|(material, _)| *material)
+            .unwrap_or_default();
+        if footing.0 != material {
+            footing.0 = material;
+        }
+    }
+}
+
+/// How a patch of ground changes the movement of actors on it.
+/// Added to a zone sensor just like [`GroundMaterial`].
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+pub struct Surface {
+    /// Multiplies the actor's acceleration. Low values make speeding up and turning slow.
+    pub acceleration: f32,
+    /// Multiplies the actor's damping. Low values make stopping slow.
+    pub damping: f32,
+    /// The fastest an actor can move on its own.
+    pub max_speed: f32,
+    /// Velocity the ground carries actors along at, like a conveyor belt.
+    pub drift: Vec2,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Self {
+            acceleration: 1.0,
+            damping: 1.0,
+            max_speed: f32::INFINITY,
+            drift: Vec2::ZERO,
+        }
+    }
+}
+
+impl Surface {
+    pub const ICE: Self = Self {
+        acceleration: 0.15,
+        damping: 0.05,
+        max_speed: f32::INFINITY,
+        drift: Vec2::ZERO,
+    };
+
+    pub const MUD: Self = Self {
+        acceleration: 1.0,
+        damping: 2.0,
+        max_speed: 120.0,
+        drift: Vec2::ZERO,
+    };
+
+    pub fn conveyor(drift: Vec2) -> Self {
+        Self { drift, ..default() }
+    }
+}
+
+/// The surface an actor is standing on.
+/// Airborne actors and actors outside of any surface zone get [`Surface::default`].
+#[derive(Component, Reflect, Clone, Copy, Default, Debug, Deref)]
+#[reflect(Component)]
+pub struct OnSurface(pub Surface);
+
+fn update_surfaces(
+    mut actor_q: Query<(Entity, &mut OnSurface, Option<&Height>)>,
+    surface_q: Query<(&Surface, &CollidingEntities)>,
+) {
+    for (entity, mut on_surface, height) in &mut actor_q {
+        let airborne = height.is_some_and(Height::is_airborne);
+        let surface = surface_q
+            .iter()
+            .find(|(_, colliding)| !airborne && colliding.contains(&entity))
+            .map(|(surface, _)| *surface)
+            .unwrap_or_default();
+        if on_surface.0 != surface {
+            on_surface.0 = surface;
+        }
+    }
+}
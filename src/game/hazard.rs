@@ -0,0 +1,176 @@
+//! Hazardous terrain and respawning.
+//! Actors that walk into a [`Pit`] fall and come back at their last [`SafePosition`],
+//! actors in [`DeepWater`] swim, and a [`Checkpoint`] sets where the player spawns.
+
+use avian2d::{collision::CollidingEntities, dynamics::rigid_body::LinearVelocity};
+use bevy::prelude::*;
+
+use super::{height::Height, spawn::player::Player};
+use crate::{screen::Screen, AppSet};
+
+/// How long falling into a pit takes before respawning, in seconds.
+const FALL_DURATION_SECS: f32 = 0.6;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Pit>();
+    app.register_type::<DeepWater>();
+    app.register_type::<Checkpoint>();
+    app.register_type::<SafePosition>();
+    app.register_type::<RespawnPoint>();
+    app.init_resource::<RespawnPoint>();
+    app.add_systems(OnEnter(Screen::Title), reset_respawn_point);
+    app.add_systems(
+        Update,
+        (
+            tick_falling.in_set(AppSet::TickTimers),
+            (
+                enter_pits,
+                update_swimming,
+                track_safe_position,
+                reach_checkpoints,
+            )
+                .chain()
+                .in_set(AppSet::Update),
+        ),
+    );
+}
+
+/// A hole that grounded actors fall into.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Pit;
+
+/// Water too deep to walk through.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct DeepWater;
+
+/// Touching it makes it the place the player spawns at.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Checkpoint;
+
+/// Where the player spawns, set by the last [`Checkpoint`] they touched.
+#[derive(Resource, Reflect, Clone, Copy, Default, Debug, Deref)]
+#[reflect(Resource)]
+pub struct RespawnPoint(pub Vec2);
+
+/// The last position an actor stood on solid ground, away from any hazard.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug, Deref)]
+#[reflect(Component)]
+pub struct SafePosition(pub Vec2);
+
+/// An actor falling into a pit. It can't move until it respawns.
+#[derive(Component, Debug)]
+pub struct Falling(Timer);
+
+impl Falling {
+    /// How far along the fall is, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        self.0.fraction()
+    }
+}
+
+/// An actor swimming in [`DeepWater`]. It moves slower and can't jump or slide.
+#[derive(Component, Debug)]
+pub struct Swimming;
+
+fn reset_respawn_point(mut respawn_point: ResMut<RespawnPoint>) {
+    *respawn_point = RespawnPoint::default();
+}
+
+fn is_grounded(height: Option<&Height>) -> bool {
+    !height.is_some_and(Height::is_airborne)
+}
+
+fn enter_pits(
+    mut commands: Commands,
+    mut actor_q: Query<
+        (Entity, &mut LinearVelocity, Option<&Height>),
+        (With<SafePosition>, Without<Falling>),
+    >,
+    pit_q: Query<&CollidingEntities, With<Pit>>,
+) {
+    for (entity, mut velocity, height) in &mut actor_q {
+        if !is_grounded(height) || !pit_q.iter().any(|pit| pit.contains(&entity)) {
+            continue;
+        }
+        velocity.0 = Vec2::ZERO;
+        commands
+            .entity(entity)
+            .insert(Falling(Timer::from_seconds(
+                FALL_DURATION_SECS,
+                TimerMode::Once,
+            )))
+            .remove::<Swimming>();
+    }
+}
+
+fn tick_falling(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut falling_q: Query<(
+        Entity,
+        &mut Falling,
+        &mut Transform,
+        &mut LinearVelocity,
+        &SafePosition,
+    )>,
+) {
+    for (entity, mut falling, mut transform, mut velocity, safe_position) in &mut falling_q {
+        velocity.0 = Vec2::ZERO;
+        if falling.0.tick(time.delta()).finished() {
+            transform.translation = safe_position.extend(transform.translation.z);
+            commands.entity(entity).remove::<Falling>();
+        }
+    }
+}
+
+fn update_swimming(
+    mut commands: Commands,
+    actor_q: Query<
+        (Entity, Option<&Height>, Has<Swimming>),
+        (With<SafePosition>, Without<Falling>),
+    >,
+    water_q: Query<&CollidingEntities, With<DeepWater>>,
+) {
+    for (entity, height, swimming) in &actor_q {
+        let in_water = is_grounded(height) && water_q.iter().any(|water| water.contains(&entity));
+        if in_water && !swimming {
+            commands.entity(entity).insert(Swimming);
+        } else if !in_water && swimming {
+            commands.entity(entity).remove::<Swimming>();
+        }
+    }
+}
+
+/// Remembers where actors last stood on solid ground, so they can respawn there.
+fn track_safe_position(
+    mut actor_q: Query<
+        (Entity, &Transform, &mut SafePosition, Option<&Height>),
+        (Without<Falling>, Without<Swimming>),
+    >,
+    hazard_q: Query<&CollidingEntities, Or<(With<Pit>, With<DeepWater>)>>,
+) {
+    for (entity, transform, mut safe_position, height) in &mut actor_q {
+        if is_grounded(height) && !hazard_q.iter().any(|hazard| hazard.contains(&entity)) {
+            safe_position.0 = transform.translation.xy();
+        }
+    }
+}
+
+fn reach_checkpoints(
+    mut respawn_point: ResMut<RespawnPoint>,
+    player_q: Query<Entity, With<Player>>,
+    checkpoint_q: Query<(&GlobalTransform, &CollidingEntities), With<Checkpoint>>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    for (transform, colliding) in &checkpoint_q {
+        let position = transform.translation().xy();
+        if colliding.contains(&player) && respawn_point.0 != position {
+            respawn_point.0 = position;
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! A fake height axis for jumping in a top-down world.
+//! Entities with [`Height`] are drawn raised off the ground above a shadow,
+//! and fly over [`PhysicsLayers::Low`] obstacles while they're in the air.
+
+use avian2d::collision::CollisionLayers;
+use bevy::prelude::*;
+
+use super::{audio::sfx::PlaySfx, camera::YSorted, physics::PhysicsLayers};
+
+/// Downwards acceleration of airborne entities, in pixels per second squared.
+const HEIGHT_GRAVITY: f32 = 900.0;
+/// How high an entity has to be for its shadow to shrink to half its size.
+const SHADOW_HALF_SIZE_HEIGHT: f32 = 24.0;
+/// Landing slower than this doesn't make a sound.
+const LANDING_SOUND_MIN_SPEED: f32 = 60.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Height>();
+    app.register_type::<JumpImpulse>();
+    app.init_resource::<ShadowAssets>();
+    app.add_systems(FixedUpdate, update_height);
+    app.add_systems(Update, (spawn_shadows, update_shadows));
+    app.observe(play_landing_sound);
+}
+
+/// How far above the ground an entity is, in pixels, and how fast that's changing.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Height {
+    pub height: f32,
+    pub velocity: f32,
+}
+
+impl Height {
+    pub fn is_airborne(&self) -> bool {
+        self.height > 0.0 || self.velocity > 0.0
+    }
+}
+
+/// Upwards velocity a character jumps with.
+#[derive(Component, Reflect, Clone, Copy, Debug, Deref)]
+#[reflect(Component)]
+pub struct JumpImpulse(pub f32);
+
+impl Default for JumpImpulse {
+    fn default() -> Self {
+        Self(220.0)
+    }
+}
+
+/// Triggered on an entity with [`Height`] when it hits the ground.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Landed {
+    /// How fast the entity was falling.
+    pub speed: f32,
+}
+
+/// The shadow on the ground below an entity with [`Height`].
+#[derive(Component, Debug)]
+struct Shadow(Entity);
+
+#[derive(Resource)]
+struct ShadowAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
+impl FromWorld for ShadowAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Ellipse::new(6.0, 2.5));
+        let material = world
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(Color::srgba(0.0, 0.0, 0.0, 0.35));
+        Self { mesh, material }
+    }
+}
+
+fn update_height(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut height_q: Query<(Entity, &mut Height, Option<&mut CollisionLayers>)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut height, layers) in &mut height_q {
+        if !height.is_airborne() {
+            continue;
+        }
+        let was_grounded = height.height <= 0.0;
+        height.velocity -= HEIGHT_GRAVITY * dt;
+        height.height += height.velocity * dt;
+
+        let landed = height.height <= 0.0;
+        if landed {
+            let speed = -height.velocity;
+            *height = Height::default();
+            commands.trigger_targets(Landed { speed }, entity);
+        }
+        if let Some(mut layers) = layers {
+            if was_grounded && !landed {
+                layers.filters.remove(PhysicsLayers::Low);
+            } else if landed {
+                layers.filters.add(PhysicsLayers::Low);
+            }
+        }
+    }
+}
+
+fn spawn_shadows(
+    mut commands: Commands,
+    shadow_assets: Res<ShadowAssets>,
+    height_q: Query<Entity, Added<Height>>,
+) {
+    for entity in &height_q {
+        commands.spawn((
+            Name::new("Shadow"),
+            Shadow(entity),
+            ColorMesh2dBundle {
+                mesh: shadow_assets.mesh.clone().into(),
+                material: shadow_assets.material.clone(),
+                ..default()
+            },
+            YSorted::default().with_priority(-1.0),
+        ));
+    }
+}
+
+/// Keeps shadows on the ground below their owners, shrinking them the higher the owner is.
+fn update_shadows(
+    mut commands: Commands,
+    mut shadow_q: Query<(Entity, &Shadow, &mut Transform)>,
+    owner_q: Query<(&Transform, &Height), Without<Shadow>>,
+) {
+    for (entity, &Shadow(owner), mut transform) in &mut shadow_q {
+        let Ok((owner_transform, height)) = owner_q.get(owner) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        transform.translation = owner_transform
+            .translation
+            .xy()
+            .extend(transform.translation.z);
+        let scale = SHADOW_HALF_SIZE_HEIGHT / (SHADOW_HALF_SIZE_HEIGHT + height.height);
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
+
+fn play_landing_sound(trigger: Trigger<Landed>, mut commands: Commands) {
+    if trigger.event().speed < LANDING_SOUND_MIN_SPEED {
+        return;
+    }
+    commands.trigger_targets(PlaySfx::Footstep, trigger.entity());
+}
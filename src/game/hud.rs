@@ -0,0 +1,107 @@
+//! The heads-up display shown while playing.
+
+use bevy::prelude::*;
+
+use super::{keys::CurrentDungeon, save::SaveData};
+use crate::{screen::Screen, ui::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Playing), spawn_hud);
+    app.add_systems(
+        Update,
+        (update_key_counter, update_rupee_counter).run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// The node all HUD elements are children of, in the top left corner of the screen.
+#[derive(Component, Debug)]
+pub struct HudRoot;
+
+/// Text showing the keys held for the current dungeon.
+#[derive(Component, Debug)]
+struct KeyCounter;
+
+/// Text showing the rupees in the player's wallet.
+#[derive(Component, Debug)]
+struct RupeeCounter;
+
+fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("HUD"),
+            HudRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Rupee Counter"),
+                RupeeCounter,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: ui_palette::LABEL_TEXT,
+                        ..default()
+                    },
+                ),
+            ));
+            children.spawn((
+                Name::new("Key Counter"),
+                KeyCounter,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: ui_palette::LABEL_TEXT,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn update_key_counter(
+    save: Res<SaveData>,
+    dungeon: Res<CurrentDungeon>,
+    mut counter_q: Query<&mut Text, With<KeyCounter>>,
+    added_q: Query<(), Added<KeyCounter>>,
+) {
+    if !save.is_changed() && !dungeon.is_changed() && added_q.is_empty() {
+        return;
+    }
+    let keys = dungeon
+        .0
+        .as_ref()
+        .map(|name| save.keys.get(name).copied().unwrap_or_default());
+    for mut text in &mut counter_q {
+        text.sections[0].value = match keys {
+            Some(keys) if keys.boss => format!("Keys: {}  Boss key", keys.small),
+            Some(keys) => format!("Keys: {}", keys.small),
+            None => String::new(),
+        };
+    }
+}
+
+fn update_rupee_counter(
+    save: Res<SaveData>,
+    mut counter_q: Query<&mut Text, With<RupeeCounter>>,
+    added_q: Query<(), Added<RupeeCounter>>,
+) {
+    if !save.is_changed() && added_q.is_empty() {
+        return;
+    }
+    for mut text in &mut counter_q {
+        text.sections[0].value = format!("Rupees: {}", save.rupees);
+    }
+}
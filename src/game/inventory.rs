@@ -0,0 +1,256 @@
+//! Items and the player's [`Inventory`].
+//! Items are defined in data, in the [`ItemCatalog`] listed in the asset manifest.
+//! Each of the [`EQUIP_SLOTS`] holds one item, used with that slot's input.
+
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    assets::ItemCatalogHandle,
+    constants::{ITEM_SLOT_1, ITEM_SLOT_2},
+    pause::gameplay_running,
+    save::SaveData,
+    spawn::player::Player,
+};
+use crate::{utils::gamepad_just_pressed, AppSet};
+
+/// How many items can be equipped at once.
+pub const EQUIP_SLOTS: usize = 2;
+/// The keys and gamepad button bound to each equip slot.
+pub const EQUIP_INPUTS: [(&[KeyCode], GamepadButtonType); EQUIP_SLOTS] = [
+    (ITEM_SLOT_1, GamepadButtonType::West),
+    (ITEM_SLOT_2, GamepadButtonType::North),
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<ItemCatalog>();
+    app.init_asset_loader::<ItemCatalogLoader>();
+    app.register_type::<Inventory>();
+    app.observe(grant_items);
+    app.add_systems(
+        Update,
+        use_equipped_items
+            .run_if(gameplay_running)
+            .in_set(AppSet::RecordInput),
+    );
+    app.add_systems(Update, save_inventory.in_set(AppSet::Update));
+}
+
+/// An item, as defined in the item catalog.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemDef {
+    /// Name used to refer to the item in data, like loot tables.
+    pub id: String,
+    /// Name shown to the player.
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Most of this item the player can hold at once.
+    #[serde(default = "default_max_stack")]
+    pub max_stack: u32,
+    /// Whether the item can be put in an equip slot.
+    #[serde(default)]
+    pub equippable: bool,
+    /// Whether using the item uses one up.
+    #[serde(default)]
+    pub consumable: bool,
+}
+
+fn default_max_stack() -> u32 {
+    1
+}
+
+/// Every item in the game, in the order they're listed in the inventory.
+#[derive(Asset, TypePath, Debug)]
+pub struct ItemCatalog {
+    items: Vec<ItemDef>,
+}
+
+impl ItemCatalog {
+    pub fn get(&self, id: &str) -> Option<&ItemDef> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ItemDef> {
+        self.items.iter()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ItemCatalogError {
+    #[error("could not read item catalog: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse item catalog: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("item catalog defines \"{0}\" more than once")]
+    DuplicateId(String),
+}
+
+#[derive(Default)]
+struct ItemCatalogLoader;
+
+impl AssetLoader for ItemCatalogLoader {
+    type Asset = ItemCatalog;
+    type Settings = ();
+    type Error = ItemCatalogError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let items: Vec<ItemDef> = ron::de::from_bytes(&bytes)?;
+        let mut ids = HashSet::new();
+        for item in &items {
+            if !ids.insert(item.id.as_str()) {
+                return Err(ItemCatalogError::DuplicateId(item.id.clone()));
+            }
+        }
+        Ok(ItemCatalog { items })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["items.ron"]
+    }
+}
+
+/// The items a character holds, by item id, and what they have equipped.
+#[derive(Component, Reflect, Clone, Default, Debug, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct Inventory {
+    #[serde(default)]
+    pub items: HashMap<String, u32>,
+    #[serde(default)]
+    pub equipped: [Option<String>; EQUIP_SLOTS],
+}
+
+impl Inventory {
+    pub fn count(&self, id: &str) -> u32 {
+        self.items.get(id).copied().unwrap_or_default()
+    }
+
+    /// Adds up to `count` of an item, without going over its stack limit.
+    /// Returns how many were actually added.
+    pub fn add(&mut self, item: &ItemDef, count: u32) -> u32 {
+        let held = self.items.entry(item.id.clone()).or_default();
+        let added = count.min(item.max_stack.saturating_sub(*held));
+        *held += added;
+        if *held == 0 {
+            self.items.remove(&item.id);
+        }
+        added
+    }
+
+    /// Removes `count` of an item if at least that many are held.
+    /// Items that run out are unequipped.
+    pub fn take(&mut self, id: &str, count: u32) -> bool {
+        let Some(held) = self.items.get_mut(id) else {
+            return false;
+        };
+        if *held < count {
+            return false;
+        }
+        *held -= count;
+        if *held == 0 {
+            self.items.remove(id);
+            for slot in &mut self.equipped {
+                if slot.as_deref() == Some(id) {
+                    *slot = None;
+                }
+            }
+        }
+        true
+    }
+
+    /// Puts an item in an equip slot. An item that's already equipped
+    /// elsewhere swaps places with the slot's current item.
+    pub fn equip(&mut self, slot: usize, id: &str) {
+        let previous = self.equipped[slot].replace(id.to_string());
+        for (other, equipped) in self.equipped.iter_mut().enumerate() {
+            if other != slot && equipped.as_deref() == Some(id) {
+                *equipped = previous.clone();
+            }
+        }
+    }
+}
+
+/// Triggered when the player is given an item.
+#[derive(Event, Debug, Clone)]
+pub struct ItemGranted {
+    /// Id of the item in the [`ItemCatalog`].
+    pub item: String,
+    pub count: u32,
+}
+
+/// Triggered on a character when they use an item.
+#[derive(Event, Debug, Clone)]
+pub struct ItemUsed {
+    pub item: String,
+}
+
+fn grant_items(
+    trigger: Trigger<ItemGranted>,
+    catalog_handle: Res<ItemCatalogHandle>,
+    catalogs: Res<Assets<ItemCatalog>>,
+    mut inventory_q: Query<&mut Inventory, With<Player>>,
+) {
+    let ItemGranted { item, count } = trigger.event();
+    let Some(item) = catalogs
+        .get(&catalog_handle.0)
+        .and_then(|catalog| catalog.get(item))
+    else {
+        error!("granted unknown item \"{item}\"");
+        return;
+    };
+    for mut inventory in &mut inventory_q {
+        inventory.add(item, *count);
+    }
+}
+
+fn use_equipped_items(
+    mut commands: Commands,
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    catalog_handle: Res<ItemCatalogHandle>,
+    catalogs: Res<Assets<ItemCatalog>>,
+    mut inventory_q: Query<(Entity, &mut Inventory), With<Player>>,
+) {
+    let Some(catalog) = catalogs.get(&catalog_handle.0) else {
+        return;
+    };
+    for (entity, mut inventory) in &mut inventory_q {
+        for (slot, (keys, button)) in EQUIP_INPUTS.into_iter().enumerate() {
+            let pressed = kb.any_just_pressed(keys.iter().copied())
+                || gamepad_just_pressed(&gamepads, &buttons, button);
+            if !pressed {
+                continue;
+            }
+            let Some(id) = inventory.equipped[slot].clone() else {
+                continue;
+            };
+            let consumable = catalog.get(&id).is_some_and(|item| item.consumable);
+            if consumable && !inventory.take(&id, 1) {
+                continue;
+            }
+            commands.trigger_targets(ItemUsed { item: id }, entity);
+        }
+    }
+}
+
+fn save_inventory(
+    mut save: ResMut<SaveData>,
+    inventory_q: Query<&Inventory, (With<Player>, Changed<Inventory>)>,
+) {
+    for inventory in &inventory_q {
+        save.inventory = inventory.clone();
+    }
+}
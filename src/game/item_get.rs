@@ -0,0 +1,182 @@
+//! The "item get" sequence: the player holds a new item up while a sting plays
+//! and a text box names it. Gameplay is paused until the text box is dismissed.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+
+use super::{
+    assets::SfxKey,
+    audio::sfx::PlaySfx,
+    pause::{GameplayPause, PauseReason},
+    player::{interact_system, PlayerSprite},
+    spawn::player::Player,
+};
+use crate::{
+    screen::Screen,
+    tween::{Ease, Repeat, Tween, TweenCommandsExt, TweenTarget},
+    ui::prelude::*,
+    AppSet,
+};
+
+/// How long the text box stays up before it can be dismissed, in seconds.
+const MIN_SHOW_SECS: f32 = 0.6;
+/// Keys that dismiss the text box.
+const DISMISS: [KeyCode; 3] = [KeyCode::KeyE, KeyCode::Space, KeyCode::Enter];
+/// How far above the player's sprite the item is held, in pixels.
+const HOLD_HEIGHT: f32 = 16.0;
+/// How far the held item bobs up and down, in pixels.
+const BOB_HEIGHT: f32 = 1.5;
+/// How long the held item takes to bob up or down, in seconds.
+const BOB_SECS: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ItemGetQueue>();
+    app.observe(queue_item_get);
+    app.add_systems(
+        Update,
+        advance_item_get
+            // The key that dismisses the text box shouldn't also interact again.
+            .after(interact_system)
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnExit(Screen::Playing), clear_item_gets);
+}
+
+/// Triggered to show the player getting an item.
+/// Several items in a row are shown one after another.
+#[derive(Event, Debug, Clone)]
+pub struct ShowItemGet {
+    pub text: String,
+}
+
+/// Marks the player while they hold up an item.
+#[derive(Component, Debug)]
+pub struct HoldingItem;
+
+#[derive(Resource, Default, Debug)]
+struct ItemGetQueue(VecDeque<String>);
+
+/// The text box of the item being shown.
+#[derive(Component, Debug)]
+struct ItemGetText {
+    /// Real time at which it was shown.
+    shown_at: Duration,
+}
+
+/// The item held over the player's head.
+#[derive(Component, Debug)]
+struct HeldItem;
+
+fn queue_item_get(trigger: Trigger<ShowItemGet>, mut queue: ResMut<ItemGetQueue>) {
+    queue.0.push_back(trigger.event().text.clone());
+}
+
+pub(super) fn advance_item_get(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    kb: Res<ButtonInput<KeyCode>>,
+    mut queue: ResMut<ItemGetQueue>,
+    mut pause: ResMut<GameplayPause>,
+    text_q: Query<(Entity, &ItemGetText)>,
+    held_q: Query<Entity, With<HeldItem>>,
+    player_q: Query<Entity, With<Player>>,
+    sprite_q: Query<Entity, With<PlayerSprite>>,
+) {
+    if let Ok((entity, text)) = text_q.get_single() {
+        let dismissable = (time.elapsed() - text.shown_at).as_secs_f32() >= MIN_SHOW_SECS;
+        if !dismissable || !kb.any_just_pressed(DISMISS) {
+            return;
+        }
+        commands.entity(entity).despawn_recursive();
+        for held in &held_q {
+            commands.entity(held).despawn_recursive();
+        }
+        if queue.0.is_empty() {
+            for player in &player_q {
+                commands.entity(player).remove::<HoldingItem>();
+            }
+            pause.remove(PauseReason::ItemGet);
+        }
+        return;
+    }
+    let Some(text) = queue.0.pop_front() else {
+        return;
+    };
+
+    pause.add(PauseReason::ItemGet);
+    commands.trigger(PlaySfx::Key(SfxKey::ItemGet));
+    for player in &player_q {
+        commands.entity(player).insert(HoldingItem);
+    }
+    let held_at = Vec3::new(0.0, HOLD_HEIGHT, 0.1);
+    // Gameplay time is stopped, so the bobbing runs on real time.
+    let bob = Tween::new(
+        BOB_SECS,
+        Ease::SineInOut,
+        TweenTarget::Translation {
+            from: held_at,
+            to: held_at + Vec3::Y * BOB_HEIGHT,
+        },
+    )
+    .yoyo()
+    .repeat(Repeat::Forever)
+    .in_real_time();
+    for sprite in &sprite_q {
+        commands.entity(sprite).with_children(|children| {
+            children
+                .spawn((
+                    Name::new("Held Item"),
+                    HeldItem,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgb(0.95, 0.8, 0.3),
+                            custom_size: Some(Vec2::splat(6.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(held_at)
+                            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                        ..default()
+                    },
+                ))
+                .tween(bob.clone());
+        });
+    }
+    commands
+        .spawn((
+            Name::new("Item Get Text Box"),
+            ItemGetText {
+                shown_at: time.elapsed(),
+            },
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(40.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(16.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(ui_palette::NODE_BACKGROUND),
+                    ..default()
+                })
+                .with_children(|children| {
+                    children.label(text);
+                });
+        });
+}
+
+fn clear_item_gets(mut queue: ResMut<ItemGetQueue>) {
+    queue.0.clear();
+}
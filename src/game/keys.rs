@@ -0,0 +1,160 @@
+//! Small keys and boss keys, which belong to one dungeon each, and the doors they unlock.
+//! Keys are kept in the [`SaveData`], so they carry over between rooms and play sessions.
+
+use avian2d::collision::CollidingEntities;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{player::Interacted, save::SaveData, spawn::player::Player};
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<KeyKind>();
+    app.register_type::<KeyPickup>();
+    app.register_type::<LockedDoor>();
+    app.register_type::<Dungeon>();
+    app.register_type::<CurrentDungeon>();
+    app.init_resource::<CurrentDungeon>();
+    app.add_systems(
+        Update,
+        (remove_collected, pick_up_keys, update_current_dungeon)
+            .chain()
+            .in_set(AppSet::Update),
+    );
+    app.observe(unlock_doors);
+}
+
+#[derive(Reflect, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum KeyKind {
+    /// Opens one locked door and is used up.
+    #[default]
+    Small,
+    /// Opens every boss door of its dungeon.
+    Boss,
+}
+
+/// The keys the player holds for one dungeon.
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct DungeonKeys {
+    pub small: u32,
+    pub boss: bool,
+}
+
+/// A key lying around, picked up by touching it.
+/// Needs a sensor collider that detects actors.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct KeyPickup {
+    pub dungeon: String,
+    pub kind: KeyKind,
+    /// Keys with an id stay picked up, even across saves.
+    pub id: Option<String>,
+}
+
+/// A door that's opened by interacting with it while holding a key of its dungeon.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct LockedDoor {
+    pub dungeon: String,
+    pub kind: KeyKind,
+    /// Doors with an id stay unlocked, even across saves.
+    pub id: Option<String>,
+}
+
+/// An area that belongs to a dungeon, marked by a sensor collider that detects actors.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct Dungeon(pub String);
+
+/// The dungeon the player is in, if any.
+#[derive(Resource, Reflect, Clone, Default, Debug)]
+#[reflect(Resource)]
+pub struct CurrentDungeon(pub Option<String>);
+
+/// Removes keys and doors that were picked up or unlocked before.
+fn remove_collected(
+    mut commands: Commands,
+    save: Res<SaveData>,
+    key_q: Query<(Entity, &KeyPickup), Added<KeyPickup>>,
+    door_q: Query<(Entity, &LockedDoor), Added<LockedDoor>>,
+) {
+    let ids = key_q
+        .iter()
+        .map(|(entity, key)| (entity, &key.id))
+        .chain(door_q.iter().map(|(entity, door)| (entity, &door.id)));
+    for (entity, id) in ids {
+        if id.as_ref().is_some_and(|id| save.collected.contains(id)) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn pick_up_keys(
+    mut commands: Commands,
+    mut save: ResMut<SaveData>,
+    player_q: Query<Entity, With<Player>>,
+    key_q: Query<(Entity, &KeyPickup, &CollidingEntities)>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    for (entity, key, colliding) in &key_q {
+        if !colliding.contains(&player) {
+            continue;
+        }
+        let keys = save.keys.entry(key.dungeon.clone()).or_default();
+        match key.kind {
+            KeyKind::Small => keys.small += 1,
+            KeyKind::Boss => keys.boss = true,
+        }
+        if let Some(id) = &key.id {
+            save.collected.insert(id.clone());
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn unlock_doors(
+    trigger: Trigger<Interacted>,
+    mut commands: Commands,
+    mut save: ResMut<SaveData>,
+    door_q: Query<&LockedDoor>,
+) {
+    let entity = trigger.entity();
+    let Ok(door) = door_q.get(entity) else {
+        return;
+    };
+    // Only borrow the save mutably once a key is used, so that trying a locked
+    // door doesn't mark it as changed.
+    let keys = save.keys.get(&door.dungeon).copied().unwrap_or_default();
+    match door.kind {
+        KeyKind::Small if keys.small > 0 => {
+            if let Some(keys) = save.keys.get_mut(&door.dungeon) {
+                keys.small -= 1;
+            }
+        }
+        KeyKind::Boss if keys.boss => {}
+        KeyKind::Small | KeyKind::Boss => return,
+    }
+    if let Some(id) = &door.id {
+        save.collected.insert(id.clone());
+    }
+    commands.entity(entity).despawn_recursive();
+}
+
+fn update_current_dungeon(
+    mut current: ResMut<CurrentDungeon>,
+    player_q: Query<Entity, With<Player>>,
+    dungeon_q: Query<(&Dungeon, &CollidingEntities)>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    let dungeon = dungeon_q
+        .iter()
+        .find(|(_, colliding)| colliding.contains(&player))
+        .map(|(dungeon, _)| dungeon.0.clone());
+    if current.0 != dungeon {
+        current.0 = dungeon;
+    }
+}
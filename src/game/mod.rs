@@ -2,13 +2,35 @@
 
 use bevy::prelude::*;
 
-mod animation;
+pub mod animation;
 pub mod assets;
 pub mod audio;
+pub mod bomb;
 pub mod camera;
+pub mod chest;
 pub mod constants;
+pub mod cutscene;
+pub mod dialogue;
+pub mod drops;
+pub mod facing;
+pub mod flags;
+pub mod ground;
+pub mod hazard;
+pub mod height;
+pub mod hud;
+pub mod inventory;
+pub mod item_get;
+pub mod keys;
+pub mod npc;
+pub mod pause;
 pub mod physics;
 pub mod player;
+pub mod puzzle;
+pub mod quest;
+pub mod save;
+pub mod shop;
+pub mod signal;
+pub mod slide;
 pub mod spawn;
 
 pub(super) fn plugin(app: &mut App) {
@@ -16,9 +38,33 @@ pub(super) fn plugin(app: &mut App) {
         animation::plugin,
         audio::plugin,
         assets::plugin,
+        bomb::plugin,
+        chest::plugin,
+        cutscene::plugin,
+        dialogue::plugin,
+        drops::plugin,
+        facing::plugin,
+        flags::plugin,
+        ground::plugin,
+        hazard::plugin,
+        height::plugin,
+        hud::plugin,
+        inventory::plugin,
+    ));
+    app.add_plugins((
+        item_get::plugin,
+        keys::plugin,
+        npc::plugin,
+        pause::plugin,
         spawn::plugin,
         physics::plugin,
         player::plugin,
+        puzzle::plugin,
+        quest::plugin,
+        save::plugin,
+        shop::plugin,
+        signal::plugin,
+        slide::plugin,
         camera::plugin,
     ));
 }
@@ -0,0 +1,220 @@
+//! Friendly characters. An [`Npc`] walks by setting its [`PlayerDir`] like the player's
+//! input does, so it moves, collides and turns like any other character.
+//! It follows its [`Route`] if it has one, or else [`Wander`]s around where it started,
+//! and turns to face the player when talked to. NPCs stand still while a dialogue or
+//! cutscene is running, unless the cutscene moves them.
+
+use avian2d::dynamics::rigid_body::LinearVelocity;
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{
+    animation::state::{update_animation_states, AnimationParams},
+    cutscene::ActiveCutscene,
+    dialogue::ActiveDialogue,
+    facing::{update_facing, Facing},
+    player::{Interacted, PlayerDir},
+    spawn::player::Player,
+};
+use crate::{screen::Screen, AppSet};
+
+/// NPCs this close to where they're going have arrived, in pixels.
+const ARRIVE_RADIUS: f32 = 2.0;
+/// NPCs that haven't arrived after this long, say because a wall is in the way,
+/// give up and rest before picking somewhere else, in seconds.
+const GIVE_UP_SECS: f32 = 6.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Npc>();
+    app.register_type::<Wander>();
+    app.register_type::<Route>();
+    app.observe(face_player_on_interact);
+    app.add_systems(
+        Update,
+        (
+            (init_npc_goals, steer_npcs)
+                .chain()
+                .in_set(AppSet::RecordInput),
+            (face_talker, update_npc_animation_params)
+                .chain()
+                .after(update_facing)
+                .before(update_animation_states)
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// A friendly character. Stands still unless it also has a [`Route`] or [`Wander`]s.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Npc;
+
+/// Walks to random spots within `radius` of where the NPC started, resting in between.
+/// NPCs pushed further away than that walk straight back.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Wander {
+    pub radius: f32,
+    /// Shortest and longest rest between walks, in seconds.
+    pub rest_secs: (f32, f32),
+}
+
+impl Default for Wander {
+    fn default() -> Self {
+        Self {
+            radius: 32.0,
+            rest_secs: (1.0, 3.0),
+        }
+    }
+}
+
+/// Walks from waypoint to waypoint, resting at each, and starts over after the last.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct Route {
+    pub waypoints: Vec<Vec2>,
+    pub rest_secs: f32,
+}
+
+/// Where an NPC is going.
+#[derive(Component, Debug, Default)]
+struct NpcGoal {
+    /// Where the NPC started, which it wanders around.
+    home: Vec2,
+    target: Option<Vec2>,
+    /// Seconds left to rest before walking on.
+    rest: f32,
+    /// Seconds spent walking toward the target.
+    walked: f32,
+    /// Index of the next waypoint on the [`Route`].
+    waypoint: usize,
+}
+
+/// The sprite of an NPC, a child of it.
+#[derive(Component, Default, Debug)]
+pub struct NpcSprite;
+
+fn init_npc_goals(mut commands: Commands, npc_q: Query<(Entity, &Transform), Added<Npc>>) {
+    for (entity, transform) in &npc_q {
+        commands.entity(entity).insert(NpcGoal {
+            home: transform.translation.xy(),
+            ..default()
+        });
+    }
+}
+
+fn steer_npcs(
+    time: Res<Time>,
+    dialogue: Option<Res<ActiveDialogue>>,
+    cutscene: Option<Res<ActiveCutscene>>,
+    mut npc_q: Query<
+        (
+            &Transform,
+            &mut PlayerDir,
+            &mut NpcGoal,
+            Option<&Wander>,
+            Option<&Route>,
+        ),
+        With<Npc>,
+    >,
+) {
+    let dt = time.delta_seconds();
+    let mut rng = rand::thread_rng();
+    for (transform, mut dir, mut goal, wander, route) in &mut npc_q {
+        dir.0 = Vec2::ZERO;
+        if dialogue.is_some() || cutscene.is_some() {
+            continue;
+        }
+        if goal.rest > 0.0 {
+            goal.rest -= dt;
+            continue;
+        }
+        let position = transform.translation.xy();
+        let route = route.filter(|route| !route.waypoints.is_empty());
+        let target = match (goal.target, route, wander) {
+            (Some(target), ..) => target,
+            (None, Some(route), _) => route.waypoints[goal.waypoint % route.waypoints.len()],
+            (None, None, Some(wander)) if position.distance(goal.home) > wander.radius => goal.home,
+            (None, None, Some(wander)) => {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let distance = wander.radius * rng.gen_range(0.0f32..1.0).sqrt();
+                goal.home + Vec2::from_angle(angle) * distance
+            }
+            (None, None, None) => continue,
+        };
+        goal.target = Some(target);
+        goal.walked += dt;
+
+        let arrived = position.distance(target) < ARRIVE_RADIUS;
+        if !arrived && goal.walked < GIVE_UP_SECS {
+            dir.0 = (target - position).normalize_or_zero();
+            continue;
+        }
+        goal.target = None;
+        goal.walked = 0.0;
+        if let Some(route) = route {
+            if arrived {
+                goal.waypoint = (goal.waypoint + 1) % route.waypoints.len();
+            }
+            goal.rest = route.rest_secs;
+        } else if let Some(wander) = wander {
+            let (min, max) = wander.rest_secs;
+            goal.rest = rng.gen_range(min..=max.max(min));
+        }
+    }
+}
+
+fn face_player_on_interact(
+    trigger: Trigger<Interacted>,
+    mut npc_q: Query<(&Transform, &mut Facing), With<Npc>>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    let (Ok((transform, mut facing)), Ok(player)) =
+        (npc_q.get_mut(trigger.entity()), player_q.get_single())
+    else {
+        return;
+    };
+    turn_toward(&mut facing, transform, player);
+}
+
+/// Keeps the NPC that's talking facing the player, even while it slows down.
+fn face_talker(
+    dialogue: Option<Res<ActiveDialogue>>,
+    mut npc_q: Query<(&Transform, &mut Facing), With<Npc>>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    let Some(talker) = dialogue.and_then(|dialogue| dialogue.talker()) else {
+        return;
+    };
+    let (Ok((transform, mut facing)), Ok(player)) = (npc_q.get_mut(talker), player_q.get_single())
+    else {
+        return;
+    };
+    turn_toward(&mut facing, transform, player);
+}
+
+fn turn_toward(facing: &mut Mut<Facing>, from: &Transform, to: &Transform) {
+    match Facing::from_direction((to.translation - from.translation).xy()) {
+        Some(next) if next != **facing => **facing = next,
+        _ => {}
+    }
+}
+
+fn update_npc_animation_params(
+    npc_q: Query<(&LinearVelocity, &Facing, &Children), With<Npc>>,
+    mut sprite_q: Query<(&mut AnimationParams, &mut Transform), With<NpcSprite>>,
+) {
+    for (LinearVelocity(velocity), &facing, children) in &npc_q {
+        let mut iter = sprite_q.iter_many_mut(children.iter());
+        while let Some((mut params, mut tf)) = iter.fetch_next() {
+            params.velocity = *velocity;
+            params.facing = facing;
+            match facing {
+                Facing::Left => tf.scale.x = -tf.scale.x.abs(),
+                Facing::Right => tf.scale.x = tf.scale.x.abs(),
+                Facing::Up | Facing::Down => {}
+            }
+        }
+    }
+}
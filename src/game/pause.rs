@@ -0,0 +1,79 @@
+//! Pausing gameplay while something else has the player's attention.
+//! While there's any [`PauseReason`] in [`GameplayPause`], the player's input is ignored.
+//! Some reasons also stop virtual time, which freezes physics, timers and animations.
+//! Anything that must keep running then, like the UI on top, should use [`Time<Real>`].
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GameplayPause>();
+    app.add_systems(Update, apply_pause);
+    app.add_systems(OnExit(Screen::Playing), clear_pause);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PauseReason {
+    /// A chest is opening in front of the player.
+    OpeningChest,
+    /// The player is showing off a new item.
+    ItemGet,
+    /// A menu is open on top of the game.
+    Menu,
+    /// The player is in a conversation.
+    Dialogue,
+    /// A cutscene is playing.
+    Cutscene,
+}
+
+impl PauseReason {
+    /// Whether the world stops while paused for this reason, rather than just the player.
+    fn stops_time(self) -> bool {
+        match self {
+            Self::OpeningChest | Self::Dialogue | Self::Cutscene => false,
+            Self::ItemGet | Self::Menu => true,
+        }
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct GameplayPause(HashSet<PauseReason>);
+
+impl GameplayPause {
+    pub fn add(&mut self, reason: PauseReason) {
+        self.0.insert(reason);
+    }
+
+    pub fn remove(&mut self, reason: PauseReason) {
+        self.0.remove(&reason);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    pub fn stops_time(&self) -> bool {
+        self.0.iter().any(|reason| reason.stops_time())
+    }
+}
+
+/// Run condition for systems that handle the player's input.
+pub fn gameplay_running(pause: Res<GameplayPause>) -> bool {
+    !pause.is_paused()
+}
+
+fn apply_pause(pause: Res<GameplayPause>, mut time: ResMut<Time<Virtual>>) {
+    if !pause.is_changed() {
+        return;
+    }
+    if pause.stops_time() {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+}
+
+fn clear_pause(mut pause: ResMut<GameplayPause>) {
+    pause.0.clear();
+}
@@ -18,15 +18,20 @@ pub(super) fn plugin(app: &mut App) {
 pub enum MovementAction {
     Move(Vector),
     Jump,
+    Slide,
 }
 
 #[derive(PhysicsLayer)]
 #[allow(dead_code)]
 pub enum PhysicsLayers {
     World,
+    /// Obstacles low enough to jump over.
+    Low,
     Actor,
     Interactable,
     Weapon,
+    /// Sensors that react to actors entering them, like music areas.
+    Zone,
     No,
 }
 
@@ -5,7 +5,6 @@ use avian2d::{
     schedule::PhysicsSet,
 };
 use bevy::{prelude::*, utils::HashSet};
-use bevy_spritesheet_animation::{component::SpritesheetAnimation, library::SpritesheetLibrary};
 
 use crate::{
     utils::{get_vec, SmoothNudge},
@@ -13,22 +12,59 @@ use crate::{
 };
 
 use super::{
+    animation::state::{
+        update_animation_states, AnimationParams, AnimationState, AnimationStateEntered,
+        AnimationStateMachine, CharacterAction, Condition, SpeedFactor,
+    },
     audio::sfx::PlaySfx,
-    constants::{DOWN, LEFT, RIGHT, UP},
+    camera::YSorted,
+    constants::{DOWN, LEFT, RIGHT, SLIDE, UP},
+    facing::{update_facing, Facing},
+    ground::OnSurface,
+    hazard::{Falling, SafePosition, Swimming},
+    height::{Height, JumpImpulse},
+    item_get::HoldingItem,
+    pause::gameplay_running,
     physics::{Damping, MovementAcceleration, MovementAction, MovementBundle},
+    slide::{start_slide, SlideCooldown, SlidePhase, SlideSettings, Sliding},
     spawn::player::Player,
 };
 
+/// Above this speed the player slides instead of walking.
+const SLIDE_SPEED: f32 = 300.0;
+/// Below this speed the player stands still.
+const IDLE_SPEED: f32 = 2.0;
+/// Characters can't swim faster than this.
+const SWIM_SPEED: f32 = 90.0;
+/// How far the player sinks into deep water, in pixels.
+const SWIM_SINK: f32 = 6.0;
+
 pub(crate) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
-        (update_anim_speed, interact_system, interact_event_printer).in_set(AppSet::Update),
+        (
+            update_player_animation_params
+                .after(update_facing)
+                .before(update_animation_states),
+            (
+                interact_system.run_if(gameplay_running),
+                dispatch_interactions,
+            )
+                .chain(),
+        )
+            .in_set(AppSet::Update),
     )
     .add_systems(
         Update,
-        (keyboard_input, set_dir).in_set(AppSet::RecordInput),
+        (
+            keyboard_input.run_if(gameplay_running),
+            (set_dir, jump, slide),
+        )
+            .chain()
+            .in_set(AppSet::RecordInput),
     )
     .add_systems(FixedUpdate, movement)
+    .observe(sync_footsteps)
     .add_systems(
         PostUpdate,
         update_player_sprite_transform
@@ -51,6 +87,7 @@ pub struct CharacterControllerBundle {
     collider: Collider,
     locked_axes: LockedAxes,
     movement: MovementBundle,
+    safe_position: SafePosition,
 }
 #[derive(Event, Clone, Copy, Debug)]
 #[allow(dead_code)]
@@ -59,12 +96,13 @@ pub enum InteractEvents {
     Exited(Entity),
     Toggled(Entity),
 }
+
+/// Triggered on an entity when the player interacts with it.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Interacted;
 #[derive(Component, Deref, DerefMut, Default)]
 pub struct PlayerDir(pub Vec2);
 
-#[derive(Component)]
-pub struct _Sliding;
-
 #[derive(Component, Default, Deref, DerefMut, Debug)]
 pub struct Interacter(HashSet<Entity>);
 
@@ -80,6 +118,7 @@ impl CharacterControllerBundle {
             collider,
             locked_axes: LockedAxes::ROTATION_LOCKED,
             movement: MovementBundle::default(),
+            safe_position: SafePosition::default(),
         }
     }
 
@@ -125,9 +164,65 @@ fn keyboard_input(
     if kb.just_pressed(KeyCode::Space) {
         movement_event_writer.send(MovementAction::Jump);
     }
+
+    if kb.any_just_pressed(SLIDE.iter().copied()) {
+        movement_event_writer.send(MovementAction::Slide);
+    }
+}
+
+fn jump(
+    mut movement_reader: EventReader<MovementAction>,
+    mut controllers: Query<
+        (&mut Height, &JumpImpulse),
+        (
+            With<CharacterController>,
+            Without<Falling>,
+            Without<Swimming>,
+        ),
+    >,
+) {
+    if !movement_reader
+        .read()
+        .any(|event| matches!(event, MovementAction::Jump))
+    {
+        return;
+    }
+    for (mut height, &JumpImpulse(impulse)) in &mut controllers {
+        if !height.is_airborne() {
+            height.velocity = impulse;
+        }
+    }
+}
+
+fn slide(
+    mut commands: Commands,
+    mut movement_reader: EventReader<MovementAction>,
+    controllers: Query<
+        (Entity, &SlideSettings, &PlayerDir, &Facing),
+        (
+            With<CharacterController>,
+            Without<Sliding>,
+            Without<SlideCooldown>,
+            Without<Falling>,
+            Without<Swimming>,
+        ),
+    >,
+) {
+    if !movement_reader
+        .read()
+        .any(|event| matches!(event, MovementAction::Slide))
+    {
+        return;
+    }
+    for (entity, settings, &PlayerDir(dir), &facing) in &controllers {
+        start_slide(&mut commands, entity, settings, dir, facing);
+    }
 }
 
-fn set_dir(mut movement_reader: EventReader<MovementAction>, mut player_q: Query<&mut PlayerDir>) {
+fn set_dir(
+    mut movement_reader: EventReader<MovementAction>,
+    mut player_q: Query<&mut PlayerDir, With<Player>>,
+) {
     let Ok(mut player_dir) = player_q.get_single_mut() else {
         return;
     };
@@ -138,66 +233,134 @@ fn set_dir(mut movement_reader: EventReader<MovementAction>, mut player_q: Query
         }
     }
 }
-fn update_anim_speed(
-    parent_q: Query<&LinearVelocity, With<Player>>,
-    mut children: Query<(&mut SpritesheetAnimation, &mut Transform), With<PlayerSprite>>,
-    library: Res<SpritesheetLibrary>,
-) {
-    for (mut anim, mut tf) in children.iter_mut() {
-        let Ok(LinearVelocity(v)) = parent_q.get_single() else {
-            continue;
-        };
-        use std::cmp::Ordering::*;
-        let id = match (
-            v.x.total_cmp(&0.0),
-            v.y.total_cmp(&0.0),
-            v.length().total_cmp(&300.0),
-        ) {
-            (_, Greater, Greater) if v.y >= 2.0 => {
-                library.animation_with_name("vertical_slide_player")
-            }
-            (Less | Greater, Less | Equal, Greater) | (Equal, Less, Greater)
-                if v.x.abs() >= 2.0 || v.y <= -2.0 =>
-            {
-                library.animation_with_name("horizontal_slide_player")
-            }
-            (_, Greater, _) if v.y >= 2.0 => library.animation_with_name("vertical_walk_player"),
-            (Less | Greater, Less | Equal, _) | (Equal, Less, _)
-                if v.x.abs() >= 2.0 || v.y <= -2.0 =>
-            {
-                library.animation_with_name("horizontal_walk_player")
-            }
-            _ => library.animation_with_name("idle_player"),
-        }
-        .unwrap();
-        if id != anim.animation_id {
-            anim.animation_id = id;
-        }
-        if v.x.abs() > 1.0 {
-            tf.scale.x = tf.scale.x.abs() * v.x.signum();
+/// The player's animations: idle, walking and sliding, drawn from the front, the back
+/// and the side, plus holding an item up. Facing left uses the side view flipped.
+pub fn player_animations() -> AnimationStateMachine {
+    let walk_speed = SpeedFactor::Falloff {
+        reference_speed: SLIDE_SPEED,
+        min: 0.1,
+        sharpness: 8,
+    };
+    let views: [(&str, &[Facing]); 3] = [
+        ("down", &[Facing::Down]),
+        ("up", &[Facing::Up]),
+        ("side", &[Facing::Left, Facing::Right]),
+    ];
+
+    let mut machine = AnimationStateMachine::new(
+        AnimationState::new("idle_down", "down_idle_player").with_speed(walk_speed),
+    );
+    for (view, _) in views {
+        if view != "down" {
+            machine = machine.with_state(
+                AnimationState::new(format!("idle_{view}"), format!("{view}_idle_player"))
+                    .with_speed(walk_speed),
+            );
         }
-        if v.length() <= 300.0 {
-            anim.speed_factor = 1.0 * 0.1f32.powf((v.length() / 300.0).powi(8));
+        machine = machine
+            .with_state(
+                AnimationState::new(format!("walk_{view}"), format!("{view}_walk_player"))
+                    .with_speed(walk_speed),
+            )
+            .with_state(AnimationState::new(
+                format!("slide_{view}"),
+                format!("{view}_slide_player"),
+            ));
+    }
+    machine = machine
+        .with_state(AnimationState::new("hold_item", "hold_item_player"))
+        .with_transition(
+            None,
+            "hold_item",
+            Condition::Action(CharacterAction::HoldItem),
+        );
+    for (view, facings) in views {
+        let facing = Condition::Any(facings.iter().map(|&f| Condition::Facing(f)).collect());
+        machine = machine
+            .with_transition(
+                None,
+                &format!("slide_{view}"),
+                Condition::All(vec![
+                    Condition::Any(vec![
+                        Condition::Action(CharacterAction::Slide),
+                        Condition::SpeedAbove(SLIDE_SPEED),
+                    ]),
+                    facing.clone(),
+                ]),
+            )
+            .with_transition(
+                None,
+                &format!("walk_{view}"),
+                Condition::All(vec![Condition::SpeedAbove(IDLE_SPEED), facing.clone()]),
+            )
+            .with_transition(None, &format!("idle_{view}"), facing);
+    }
+    machine
+}
+
+fn update_player_animation_params(
+    player_q: Query<(&LinearVelocity, &Facing, Has<Sliding>, Has<HoldingItem>), With<Player>>,
+    mut sprite_q: Query<(&mut AnimationParams, &mut Transform), With<PlayerSprite>>,
+) {
+    let Ok((LinearVelocity(velocity), &facing, sliding, holding)) = player_q.get_single() else {
+        return;
+    };
+    for (mut params, mut tf) in &mut sprite_q {
+        params.velocity = *velocity;
+        params.facing = facing;
+        params.action = if holding {
+            Some(CharacterAction::HoldItem)
         } else {
-            anim.speed_factor = 1.0;
+            sliding.then_some(CharacterAction::Slide)
+        };
+        match facing {
+            Facing::Left => tf.scale.x = -tf.scale.x.abs(),
+            Facing::Right => tf.scale.x = tf.scale.x.abs(),
+            Facing::Up | Facing::Down => {}
         }
     }
 }
 
+/// Starting to walk takes a step right away, so steps line up with the walk cycle.
+fn sync_footsteps(
+    trigger: Trigger<AnimationStateEntered>,
+    sprite_q: Query<(), With<PlayerSprite>>,
+    mut footstep_q: Query<&mut FootstepSound, With<Player>>,
+) {
+    if !trigger.event().0.starts_with("walk") || sprite_q.get(trigger.entity()).is_err() {
+        return;
+    }
+    for mut footsteps in &mut footstep_q {
+        footsteps.0 = footsteps.1;
+    }
+}
+
 fn update_player_sprite_transform(
-    mut sp_q: Query<&mut Transform, With<PlayerSprite>>,
-    p_q: Query<&Transform, (Without<PlayerSprite>, With<Player>)>,
+    mut sp_q: Query<(&mut Transform, &mut YSorted), With<PlayerSprite>>,
+    p_q: Query<
+        (&Transform, &Height, Option<&Falling>, Has<Swimming>),
+        (Without<PlayerSprite>, With<Player>),
+    >,
 ) {
-    let (Ok(mut sp_xf), Ok(Transform { translation, .. })) =
-        (sp_q.get_single_mut(), p_q.get_single())
+    let (
+        Ok((mut sp_xf, mut y_sorted)),
+        Ok((Transform { translation, .. }, height, falling, swimming)),
+    ) = (sp_q.get_single_mut(), p_q.get_single())
     else {
         return;
     };
 
-    sp_xf.translation = translation.xy().extend(sp_xf.translation.z);
+    let sink = if swimming { SWIM_SINK } else { 0.0 };
+    let offset = height.height - sink;
+    sp_xf.translation = (translation.xy() + Vec2::Y * offset).extend(sp_xf.translation.z);
+    y_sorted.set_offset(offset);
+
+    // Shrink into the pit while falling.
+    let size = falling.map_or(1.0, |falling| 1.0 - falling.fraction());
+    sp_xf.scale = Vec3::new(sp_xf.scale.x.signum() * size, size, 1.0);
 }
 
-fn interact_system(
+pub(crate) fn interact_system(
     player_q: Query<&Transform, With<Player>>,
     mut interact_q: Query<(&Parent, &CollidingEntities, &mut Interacter), With<Sensor>>,
     interacated_q: Query<&Transform, Without<Player>>,
@@ -259,9 +422,11 @@ fn interact_system(
     }
 }
 
-fn interact_event_printer(mut reader: EventReader<InteractEvents>) {
+fn dispatch_interactions(mut commands: Commands, mut reader: EventReader<InteractEvents>) {
     for event in reader.read() {
-        info!("got event {event:?}")
+        if let InteractEvents::Toggled(entity) = *event {
+            commands.trigger_targets(Interacted, entity);
+        }
     }
 }
 fn movement(
@@ -269,13 +434,18 @@ fn movement(
     mut commands: Commands,
     mut controllers: Query<
         (
+            Entity,
             &MovementAcceleration,
             &mut LinearVelocity,
             Option<&Damping>,
             &PlayerDir,
             &mut FootstepSound,
+            Option<(&mut Sliding, &SlideSettings)>,
+            Option<&OnSurface>,
+            Option<&Height>,
+            Has<Swimming>,
         ),
-        With<CharacterController>,
+        (With<CharacterController>, Without<Falling>),
     >,
 ) {
     // Precision is adjusted so that the example works with
@@ -283,6 +453,7 @@ fn movement(
     let delta_time = time.delta_seconds();
 
     for (
+        entity,
         &MovementAcceleration {
             acceleration,
             max_speed,
@@ -291,22 +462,46 @@ fn movement(
         damp,
         &PlayerDir(dir),
         mut footsteps,
+        sliding,
+        surface,
+        height,
+        swimming,
     ) in &mut controllers
     {
-        linear_velocity
-            .0
-            .smooth_nudge(&(dir * max_speed), acceleration, delta_time);
-        if linear_velocity.length() < 300.0 {
-            footsteps.0 += linear_velocity.length() * delta_time;
+        let surface = surface.map(|s| s.0).unwrap_or_default();
+        let max_speed = if swimming {
+            max_speed.min(SWIM_SPEED)
+        } else {
+            max_speed
+        };
+        // Sliding characters ignore the usual movement until they've recovered.
+        let dir = match sliding {
+            Some((mut sliding, settings)) if sliding.phase == SlidePhase::Dashing => {
+                sliding.steer(&mut linear_velocity.0, dir, settings.steering, delta_time);
+                continue;
+            }
+            Some(_) => Vec2::ZERO,
+            None => dir,
+        };
+        linear_velocity.0.smooth_nudge(
+            &(dir * max_speed.min(surface.max_speed) + surface.drift),
+            acceleration * surface.acceleration,
+            delta_time,
+        );
+        // Only walking on the ground makes steps, not being carried along or flying through the air.
+        let walked = (linear_velocity.0 - surface.drift).length();
+        let grounded = !height.is_some_and(Height::is_airborne);
+        if grounded && dir != Vec2::ZERO && walked < SLIDE_SPEED {
+            footsteps.0 += walked * delta_time;
         }
         if footsteps.0 >= footsteps.1 {
             footsteps.0 = 0.0;
-            commands.trigger(PlaySfx::RandomStep);
+            commands.trigger_targets(PlaySfx::Footstep, entity);
         }
         if let (Some(Damping(damp)), true) = (damp, dir.length() == 0.0) {
             linear_velocity
                 .0
-                .smooth_nudge(&Vector::ZERO, *damp, delta_time);
+                .smooth_nudge(&surface.drift, *damp * surface.damping, delta_time);
         }
     }
 }
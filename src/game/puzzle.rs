@@ -0,0 +1,143 @@
+//! Grid-based puzzle objects.
+//! A [`Pushable`] block moves one tile when a character keeps pushing against it,
+//! as long as nothing on [`PhysicsLayers::World`] is in the way.
+
+use avian2d::{
+    collision::{Collider, Collisions},
+    spatial_query::{SpatialQuery, SpatialQueryFilter},
+};
+use bevy::prelude::*;
+
+use super::{
+    constants::TILE_SIZE,
+    facing::Facing,
+    physics::PhysicsLayers,
+    player::{CharacterController, PlayerDir},
+};
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Pushable>();
+    app.add_systems(
+        Update,
+        (push_blocks, move_pushed_blocks)
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// A block that moves one tile when pushed for [`Pushable::delay`] seconds.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Pushable {
+    /// How long a character has to push before the block moves, in seconds.
+    pub delay: f32,
+    /// How long moving one tile takes, in seconds.
+    pub duration: f32,
+    #[reflect(ignore)]
+    push: Option<(Facing, f32)>,
+}
+
+impl Default for Pushable {
+    fn default() -> Self {
+        Self {
+            delay: 0.4,
+            duration: 0.3,
+            push: None,
+        }
+    }
+}
+
+/// A pushed block on its way to the next tile.
+#[derive(Component, Debug)]
+struct PushMotion {
+    from: Vec2,
+    to: Vec2,
+    timer: Timer,
+}
+
+/// The grid cell containing `position`.
+pub fn tile_of(position: Vec2) -> IVec2 {
+    (position / TILE_SIZE).round().as_ivec2()
+}
+
+/// The center of a grid cell.
+pub fn tile_center(tile: IVec2) -> Vec2 {
+    tile.as_vec2() * TILE_SIZE
+}
+
+fn push_blocks(
+    time: Res<Time>,
+    mut commands: Commands,
+    collisions: Res<Collisions>,
+    spatial_query: SpatialQuery,
+    pusher_q: Query<(Entity, &Transform, &PlayerDir), With<CharacterController>>,
+    mut block_q: Query<(Entity, &Transform, &mut Pushable), Without<PushMotion>>,
+) {
+    for (block, block_transform, mut pushable) in &mut block_q {
+        let block_pos = block_transform.translation.xy();
+        // Only straight pushes from a character lined up with the block count.
+        let push = pusher_q.iter().find_map(|(pusher, transform, dir)| {
+            let touching = collisions
+                .get(pusher, block)
+                .is_some_and(|contacts| !contacts.manifolds.is_empty());
+            if !touching || (dir.x != 0.0 && dir.y != 0.0) {
+                return None;
+            }
+            let facing = Facing::from_direction(dir.0)?;
+            let offset = block_pos - transform.translation.xy();
+            let direction = facing.as_vec2();
+            let lined_up =
+                offset.dot(direction) > 0.0 && offset.perp_dot(direction).abs() < TILE_SIZE * 0.5;
+            lined_up.then_some(facing)
+        });
+
+        let Some(facing) = push else {
+            pushable.push = None;
+            continue;
+        };
+        let elapsed = match pushable.push {
+            Some((pushed, elapsed)) if pushed == facing => elapsed + time.delta_seconds(),
+            _ => 0.0,
+        };
+        if elapsed < pushable.delay {
+            pushable.push = Some((facing, elapsed));
+            continue;
+        }
+        pushable.push = None;
+
+        let from = tile_center(tile_of(block_pos));
+        let to = from + facing.as_vec2() * TILE_SIZE;
+        let blocked = !spatial_query
+            .shape_intersections(
+                &Collider::rectangle(TILE_SIZE * 0.9, TILE_SIZE * 0.9),
+                to,
+                0.0,
+                SpatialQueryFilter::from_mask(PhysicsLayers::World).with_excluded_entities([block]),
+            )
+            .is_empty();
+        if blocked {
+            continue;
+        }
+        commands.entity(block).insert(PushMotion {
+            from,
+            to,
+            timer: Timer::from_seconds(pushable.duration, TimerMode::Once),
+        });
+    }
+}
+
+fn move_pushed_blocks(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut block_q: Query<(Entity, &mut Transform, &mut PushMotion)>,
+) {
+    for (entity, mut transform, mut motion) in &mut block_q {
+        motion.timer.tick(time.delta());
+        let position = motion.from.lerp(motion.to, motion.timer.fraction());
+        transform.translation = position.extend(transform.translation.z);
+        if motion.timer.finished() {
+            commands.entity(entity).remove::<PushMotion>();
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Quests: tasks made of stages, each done once all of its objectives are.
+//! Quests are defined in data, in the [`QuestCatalog`] listed in the asset manifest,
+//! and objectives are [`Requirement`]s on the game's state, like a flag being set.
+//! Progress is kept in the [`SaveData`].
+
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashSet,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    assets::QuestCatalogHandle,
+    flags::{FlagValue, Requirement},
+    save::SaveData,
+};
+use crate::{screen::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<QuestCatalog>();
+    app.init_asset_loader::<QuestCatalogLoader>();
+    app.add_systems(
+        Update,
+        update_quests
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// A quest, as defined in the quest catalog.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuestDef {
+    /// Name used to refer to the quest in data, like requirements.
+    pub id: String,
+    /// Name shown to the player.
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The quest starts once this holds. Quests without one start right away.
+    #[serde(default)]
+    pub starts_when: Option<Requirement>,
+    pub stages: Vec<QuestStage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuestStage {
+    /// Shown in the quest log while this is the current stage.
+    pub description: String,
+    pub objectives: Vec<Objective>,
+    /// Flags set when the stage is done.
+    #[serde(default)]
+    pub set: Vec<(String, FlagValue)>,
+}
+
+/// Done once `done_when` holds, and stays done after that.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Objective {
+    pub description: String,
+    pub done_when: Requirement,
+}
+
+/// Every quest in the game, in the order they're listed in the quest log.
+#[derive(Asset, TypePath, Debug)]
+pub struct QuestCatalog {
+    quests: Vec<QuestDef>,
+}
+
+impl QuestCatalog {
+    pub fn iter(&self) -> impl Iterator<Item = &QuestDef> {
+        self.quests.iter()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum QuestCatalogError {
+    #[error("could not read quest catalog: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse quest catalog: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("quest catalog defines \"{0}\" more than once")]
+    DuplicateId(String),
+    #[error("quest \"{0}\" has no stages")]
+    NoStages(String),
+}
+
+#[derive(Default)]
+struct QuestCatalogLoader;
+
+impl AssetLoader for QuestCatalogLoader {
+    type Asset = QuestCatalog;
+    type Settings = ();
+    type Error = QuestCatalogError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let quests: Vec<QuestDef> = ron::de::from_bytes(&bytes)?;
+        let mut ids = HashSet::new();
+        for quest in &quests {
+            if !ids.insert(quest.id.as_str()) {
+                return Err(QuestCatalogError::DuplicateId(quest.id.clone()));
+            }
+            if quest.stages.is_empty() {
+                return Err(QuestCatalogError::NoStages(quest.id.clone()));
+            }
+        }
+        Ok(QuestCatalog { quests })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["quests.ron"]
+    }
+}
+
+/// How far along a started quest is.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct QuestProgress {
+    /// Index of the current stage.
+    pub stage: usize,
+    /// Indices of the current stage's objectives that are done.
+    #[serde(default)]
+    pub objectives: HashSet<usize>,
+    #[serde(default)]
+    pub complete: bool,
+}
+
+/// Triggered when a quest starts.
+#[derive(Event, Debug, Clone)]
+pub struct QuestStarted {
+    pub quest: String,
+}
+
+/// Triggered when an objective of a quest is done.
+#[derive(Event, Debug, Clone)]
+pub struct ObjectiveCompleted {
+    pub quest: String,
+    pub stage: usize,
+    pub objective: usize,
+}
+
+/// Triggered when the last stage of a quest is done.
+#[derive(Event, Debug, Clone)]
+pub struct QuestCompleted {
+    pub quest: String,
+}
+
+fn update_quests(
+    mut commands: Commands,
+    mut save: ResMut<SaveData>,
+    catalog_handle: Res<QuestCatalogHandle>,
+    catalogs: Res<Assets<QuestCatalog>>,
+) {
+    let Some(catalog) = catalogs.get(&catalog_handle.0) else {
+        return;
+    };
+    // Only mark the save as changed when a quest made progress, so it isn't written every frame.
+    let mut changed = false;
+    let data = save.bypass_change_detection();
+    for quest in catalog.iter() {
+        if !data.quests.contains_key(&quest.id) {
+            let starts = quest
+                .starts_when
+                .as_ref()
+                .map_or(true, |when| when.holds(data));
+            if !starts {
+                continue;
+            }
+            data.quests
+                .insert(quest.id.clone(), QuestProgress::default());
+            commands.trigger(QuestStarted {
+                quest: quest.id.clone(),
+            });
+            changed = true;
+        }
+        changed |= advance_quest(&mut commands, quest, data);
+    }
+    if changed {
+        save.set_changed();
+    }
+}
+
+/// Completes the objectives of the quest's current stage that hold,
+/// and moves on to the next stage for as long as every objective is done.
+/// Returns whether the quest made any progress.
+fn advance_quest(commands: &mut Commands, quest: &QuestDef, save: &mut SaveData) -> bool {
+    let mut changed = false;
+    loop {
+        let Some(progress) = save.quests.get(&quest.id) else {
+            return changed;
+        };
+        if progress.complete {
+            return changed;
+        }
+        let stage_index = progress.stage;
+        let Some(stage) = quest.stages.get(stage_index) else {
+            // The quest lost stages since the save was made.
+            if let Some(progress) = save.quests.get_mut(&quest.id) {
+                progress.complete = true;
+            }
+            return true;
+        };
+        let newly_done: Vec<usize> = (0..stage.objectives.len())
+            .filter(|index| {
+                !progress.objectives.contains(index)
+                    && stage.objectives[*index].done_when.holds(save)
+            })
+            .collect();
+        let all_done = progress.objectives.len() + newly_done.len() >= stage.objectives.len();
+        if newly_done.is_empty() && !all_done {
+            return changed;
+        }
+
+        let Some(progress) = save.quests.get_mut(&quest.id) else {
+            return changed;
+        };
+        changed = true;
+        for objective in newly_done {
+            progress.objectives.insert(objective);
+            commands.trigger(ObjectiveCompleted {
+                quest: quest.id.clone(),
+                stage: stage_index,
+                objective,
+            });
+        }
+        if !all_done {
+            return true;
+        }
+        progress.stage += 1;
+        progress.objectives.clear();
+        progress.complete = progress.stage >= quest.stages.len();
+        let complete = progress.complete;
+        for (flag, value) in &stage.set {
+            save.set_flag(flag.clone(), *value);
+        }
+        if complete {
+            commands.trigger(QuestCompleted {
+                quest: quest.id.clone(),
+            });
+            return true;
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! The save file. [`SaveData`] is read when the game starts and written whenever it changes.
+//! Saving to disk is only supported on desktop; elsewhere progress lasts until the game closes.
+
+use bevy::{
+    asset::ron,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{flags::FlagValue, inventory::Inventory, keys::DungeonKeys, quest::QuestProgress};
+
+/// Path of the save file, relative to the working directory.
+#[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+const SAVE_PATH: &str = "save.ron";
+/// The most rupees the player can carry.
+pub const MAX_RUPEES: u32 = 999;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(read_save());
+    app.add_systems(Last, write_save.run_if(resource_changed::<SaveData>));
+}
+
+/// Everything that persists between play sessions.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct SaveData {
+    /// Ids of puzzles that stay solved.
+    #[serde(default)]
+    pub solved: HashSet<String>,
+    /// Ids of keys that were picked up and doors that were unlocked.
+    #[serde(default)]
+    pub collected: HashSet<String>,
+    /// Keys held for every dungeon, by dungeon name.
+    #[serde(default)]
+    pub keys: HashMap<String, DungeonKeys>,
+    /// The player's items.
+    #[serde(default)]
+    pub inventory: Inventory,
+    #[serde(default)]
+    pub rupees: u32,
+    /// Game flags, by name. See [`FlagValue`].
+    #[serde(default)]
+    pub flags: HashMap<String, FlagValue>,
+    /// Progress of every started quest, by quest id.
+    #[serde(default)]
+    pub quests: HashMap<String, QuestProgress>,
+}
+
+impl SaveData {
+    /// Adds rupees, up to [`MAX_RUPEES`].
+    pub fn add_rupees(&mut self, rupees: u32) {
+        self.rupees = self.rupees.saturating_add(rupees).min(MAX_RUPEES);
+    }
+
+    pub fn flag(&self, name: &str) -> Option<FlagValue> {
+        self.flags.get(name).copied()
+    }
+
+    pub fn set_flag(&mut self, name: impl Into<String>, value: FlagValue) {
+        self.flags.insert(name.into(), value);
+    }
+}
+
+fn read_save() -> SaveData {
+    #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+    match std::fs::read_to_string(SAVE_PATH) {
+        Ok(text) => match ron::from_str(&text) {
+            Ok(save) => return save,
+            Err(e) => error!("could not parse save file \"{SAVE_PATH}\": {e}"),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => error!("could not read save file \"{SAVE_PATH}\": {e}"),
+    }
+    SaveData::default()
+}
+
+fn write_save(save: Res<SaveData>) {
+    #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+    {
+        let text = match ron::ser::to_string_pretty(&*save, default()) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("could not serialize save data: {e}");
+                return;
+            }
+        };
+        if let Err(e) = std::fs::write(SAVE_PATH, text) {
+            error!("could not write save file \"{SAVE_PATH}\": {e}");
+        }
+    }
+    #[cfg(any(target_arch = "wasm32", target_os = "android"))]
+    let _ = save;
+}
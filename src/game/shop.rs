@@ -0,0 +1,137 @@
+//! Shops. Interacting with a [`Shopkeeper`] opens their shop, whose stock is defined in data,
+//! in the [`ShopCatalog`] listed in the asset manifest.
+
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    inventory::{Inventory, ItemCatalog},
+    player::Interacted,
+    save::SaveData,
+};
+use crate::screen::Menu;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<ShopCatalog>();
+    app.init_asset_loader::<ShopCatalogLoader>();
+    app.register_type::<Shopkeeper>();
+    app.init_resource::<OpenShop>();
+    app.observe(open_shops);
+}
+
+/// Something for sale.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShopEntry {
+    /// Id of the item in the [`ItemCatalog`].
+    pub item: String,
+    /// How many of the item one purchase gives.
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// Price in rupees.
+    pub price: u32,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// The stock of every shop, by shop name.
+#[derive(Asset, TypePath, Debug)]
+pub struct ShopCatalog {
+    shops: HashMap<String, Vec<ShopEntry>>,
+}
+
+impl ShopCatalog {
+    pub fn get(&self, shop: &str) -> Option<&[ShopEntry]> {
+        self.shops.get(shop).map(Vec::as_slice)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ShopCatalogError {
+    #[error("could not read shop catalog: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse shop catalog: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+struct ShopCatalogLoader;
+
+impl AssetLoader for ShopCatalogLoader {
+    type Asset = ShopCatalog;
+    type Settings = ();
+    type Error = ShopCatalogError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let shops = ron::de::from_bytes(&bytes)?;
+        Ok(ShopCatalog { shops })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["shops.ron"]
+    }
+}
+
+/// Sells the stock of a shop in the [`ShopCatalog`], by shop name.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct Shopkeeper(pub String);
+
+/// The shop whose menu is open, if any.
+#[derive(Resource, Default, Debug)]
+pub struct OpenShop(pub Option<String>);
+
+#[derive(Debug, Error)]
+pub enum PurchaseError {
+    #[error("You don't have enough rupees.")]
+    TooExpensive,
+    #[error("You can't carry any more of that.")]
+    Full,
+    #[error("That's not for sale.")]
+    UnknownItem,
+}
+
+/// Buys `entry`, paying from the save's rupees and putting the item in `inventory`.
+pub fn buy(
+    entry: &ShopEntry,
+    save: &mut SaveData,
+    inventory: &mut Inventory,
+    catalog: &ItemCatalog,
+) -> Result<(), PurchaseError> {
+    let item = catalog.get(&entry.item).ok_or(PurchaseError::UnknownItem)?;
+    if save.rupees < entry.price {
+        return Err(PurchaseError::TooExpensive);
+    }
+    if inventory.count(&item.id) + entry.count > item.max_stack {
+        return Err(PurchaseError::Full);
+    }
+    save.rupees -= entry.price;
+    inventory.add(item, entry.count);
+    Ok(())
+}
+
+fn open_shops(
+    trigger: Trigger<Interacted>,
+    shopkeeper_q: Query<&Shopkeeper>,
+    mut open_shop: ResMut<OpenShop>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    let Ok(shopkeeper) = shopkeeper_q.get(trigger.entity()) else {
+        return;
+    };
+    open_shop.0 = Some(shopkeeper.0.clone());
+    next_menu.set(Menu::Shop);
+}
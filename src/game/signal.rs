@@ -0,0 +1,271 @@
+//! Signals that wire puzzle pieces together.
+//! An [`Emitter`] sends a named signal while it's active: pressure plates while something
+//! stands on them, levers after being interacted with and crystals after being hit.
+//! A [`FlagEmitter`] is active while something about the game's state holds, like a flag
+//! set by a dialogue or a finished quest.
+//! A [`Receiver`] combines the signals it listens to and drives a door, bridge or spawner.
+//! Signal names are plain strings, so level data can connect any emitter to any receiver.
+
+use avian2d::{
+    collision::{Collider, CollidingEntities, CollisionLayers, Sensor},
+    dynamics::rigid_body::RigidBody,
+};
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{
+    constants::TILE_SIZE, flags::Requirement, hazard::Pit, physics::PhysicsLayers,
+    player::Interacted, puzzle::Pushable, save::SaveData,
+};
+use crate::{screen::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Emitter>();
+    app.register_type::<PressurePlate>();
+    app.register_type::<Lever>();
+    app.register_type::<Crystal>();
+    app.register_type::<FlagEmitter>();
+    app.register_type::<Receiver>();
+    app.register_type::<Door>();
+    app.register_type::<Bridge>();
+    app.register_type::<Spawner>();
+    app.add_systems(
+        Update,
+        (
+            (update_pressure_plates, hit_crystals, update_flag_emitters),
+            update_receivers,
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+    app.observe(toggle_levers);
+    app.observe(open_doors);
+    app.observe(extend_bridges);
+    app.observe(spawn_from_spawners);
+}
+
+/// Sends `signal` while active.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct Emitter {
+    pub signal: String,
+    pub active: bool,
+}
+
+/// An [`Emitter`] that's active while an actor or block is on it.
+/// Needs a sensor collider on [`PhysicsLayers::Zone`] that detects actors and the world.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct PressurePlate;
+
+/// An [`Emitter`] that's switched on and off by interacting with it.
+/// Needs a collider on [`PhysicsLayers::Interactable`].
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Lever;
+
+/// An [`Emitter`] that's switched on and off by hitting it with a weapon, like a bomb blast.
+/// Needs a sensor collider that detects [`PhysicsLayers::Weapon`].
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Crystal {
+    #[reflect(ignore)]
+    hit: bool,
+}
+
+/// An [`Emitter`] that's active while its [`Requirement`] holds.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct FlagEmitter(pub Requirement);
+
+/// How a [`Receiver`] combines its inputs.
+#[derive(Reflect, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Logic {
+    /// Active while every input is active.
+    #[default]
+    All,
+    /// Active while any input is active.
+    Any,
+    /// Switches on or off every time any input becomes active.
+    Toggle,
+}
+
+/// Becomes active depending on the signals named in `inputs`.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[reflect(Component)]
+pub struct Receiver {
+    pub inputs: Vec<String>,
+    pub logic: Logic,
+    /// Once active, a receiver with an id stays active for good, even across saves.
+    pub id: Option<String>,
+    pub active: bool,
+    #[reflect(ignore)]
+    inputs_active: bool,
+}
+
+/// Triggered on a [`Receiver`] when it turns on or off, and once when it's spawned.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ReceiverChanged {
+    pub active: bool,
+}
+
+/// A [`Receiver`] that blocks the way until active.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Door;
+
+/// A [`Receiver`] over a pit that can only be crossed while active.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Bridge;
+
+/// A [`Receiver`] that spawns a pushable block every time it becomes active.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Spawner;
+
+fn update_pressure_plates(
+    mut plate_q: Query<(&mut Emitter, &CollidingEntities), With<PressurePlate>>,
+) {
+    for (mut emitter, colliding) in &mut plate_q {
+        let pressed = !colliding.is_empty();
+        if emitter.active != pressed {
+            emitter.active = pressed;
+        }
+    }
+}
+
+fn toggle_levers(trigger: Trigger<Interacted>, mut lever_q: Query<&mut Emitter, With<Lever>>) {
+    if let Ok(mut emitter) = lever_q.get_mut(trigger.entity()) {
+        emitter.active = !emitter.active;
+    }
+}
+
+fn hit_crystals(mut crystal_q: Query<(&mut Emitter, &mut Crystal, &CollidingEntities)>) {
+    for (mut emitter, mut crystal, colliding) in &mut crystal_q {
+        let hit = !colliding.is_empty();
+        if hit && !crystal.hit {
+            emitter.active = !emitter.active;
+        }
+        if crystal.hit != hit {
+            crystal.hit = hit;
+        }
+    }
+}
+
+fn update_flag_emitters(save: Res<SaveData>, mut emitter_q: Query<(&mut Emitter, &FlagEmitter)>) {
+    for (mut emitter, FlagEmitter(requirement)) in &mut emitter_q {
+        let active = requirement.holds(&save);
+        if emitter.active != active {
+            emitter.active = active;
+        }
+    }
+}
+
+fn update_receivers(
+    mut commands: Commands,
+    mut save: ResMut<SaveData>,
+    emitter_q: Query<&Emitter>,
+    mut receiver_q: Query<(Entity, &mut Receiver)>,
+) {
+    let mut signals = HashMap::<&str, bool>::default();
+    for emitter in &emitter_q {
+        *signals.entry(emitter.signal.as_str()).or_default() |= emitter.active;
+    }
+    let is_on = |input: &String| signals.get(input.as_str()).copied().unwrap_or_default();
+
+    for (entity, mut receiver) in &mut receiver_q {
+        let solved = receiver
+            .id
+            .as_ref()
+            .is_some_and(|id| save.solved.contains(id));
+        let inputs_active = match receiver.logic {
+            Logic::All => receiver.inputs.iter().all(is_on),
+            Logic::Any | Logic::Toggle => receiver.inputs.iter().any(is_on),
+        };
+        let active = solved
+            || match receiver.logic {
+                Logic::All | Logic::Any => inputs_active,
+                Logic::Toggle if inputs_active && !receiver.inputs_active => !receiver.active,
+                Logic::Toggle => receiver.active,
+            };
+        receiver.inputs_active = inputs_active;
+
+        if active != receiver.active || receiver.is_added() {
+            receiver.active = active;
+            commands.trigger_targets(ReceiverChanged { active }, entity);
+        }
+        if let (true, Some(id)) = (active && !solved, &receiver.id) {
+            save.solved.insert(id.clone());
+        }
+    }
+}
+
+fn open_doors(
+    trigger: Trigger<ReceiverChanged>,
+    mut commands: Commands,
+    mut door_q: Query<&mut Visibility, With<Door>>,
+) {
+    let entity = trigger.entity();
+    let Ok(mut visibility) = door_q.get_mut(entity) else {
+        return;
+    };
+    if trigger.event().active {
+        *visibility = Visibility::Hidden;
+        commands.entity(entity).insert(Sensor);
+    } else {
+        *visibility = Visibility::Inherited;
+        commands.entity(entity).remove::<Sensor>();
+    }
+}
+
+fn extend_bridges(
+    trigger: Trigger<ReceiverChanged>,
+    mut commands: Commands,
+    mut bridge_q: Query<&mut Visibility, With<Bridge>>,
+) {
+    let entity = trigger.entity();
+    let Ok(mut visibility) = bridge_q.get_mut(entity) else {
+        return;
+    };
+    if trigger.event().active {
+        *visibility = Visibility::Inherited;
+        commands.entity(entity).remove::<Pit>();
+    } else {
+        *visibility = Visibility::Hidden;
+        commands.entity(entity).insert(Pit);
+    }
+}
+
+fn spawn_from_spawners(
+    trigger: Trigger<ReceiverChanged>,
+    mut commands: Commands,
+    spawner_q: Query<&GlobalTransform, With<Spawner>>,
+) {
+    let Ok(transform) = spawner_q.get(trigger.entity()) else {
+        return;
+    };
+    if !trigger.event().active {
+        return;
+    }
+    commands.spawn((
+        Name::new("Block"),
+        Pushable::default(),
+        RigidBody::Static,
+        Collider::rectangle(TILE_SIZE, TILE_SIZE),
+        // Pressure plates are zones, which only notice blocks that collide with zones.
+        CollisionLayers::new(
+            PhysicsLayers::World,
+            [PhysicsLayers::Actor, PhysicsLayers::Zone],
+        ),
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.45, 0.4, 0.35),
+                custom_size: Some(Vec2::splat(TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(transform.translation()),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+}
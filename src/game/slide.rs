@@ -0,0 +1,179 @@
+//! Sliding: a short dash in one direction that can barely be steered,
+//! bounces off walls and leaves the character recovering for a moment.
+
+use avian2d::{
+    collision::{CollisionLayers, Collisions},
+    dynamics::rigid_body::{LinearVelocity, Rotation},
+};
+use bevy::prelude::*;
+
+use super::{facing::Facing, physics::PhysicsLayers};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SlideSettings>();
+    app.register_type::<SlideCooldown>();
+    app.add_systems(FixedUpdate, (tick_slides, bounce_off_walls).chain());
+}
+
+/// Tunables for characters that can slide.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct SlideSettings {
+    /// Speed at the start of a slide.
+    pub speed: f32,
+    /// How long the slide lasts, in seconds.
+    pub duration: f32,
+    /// How quickly input turns the slide. Walking turns at the controller's acceleration.
+    pub steering: f32,
+    /// Fraction of the speed kept when bouncing off a wall.
+    pub bounce: f32,
+    /// How long the character can't move after sliding, in seconds.
+    pub recovery: f32,
+    /// Time from the start of one slide until the next one is possible, in seconds.
+    pub cooldown: f32,
+}
+
+impl Default for SlideSettings {
+    fn default() -> Self {
+        Self {
+            speed: 650.0,
+            duration: 0.3,
+            steering: 1.5,
+            bounce: 0.8,
+            recovery: 0.2,
+            cooldown: 0.8,
+        }
+    }
+}
+
+/// A character in the middle of a slide.
+#[derive(Component, Debug)]
+pub struct Sliding {
+    pub phase: SlidePhase,
+    pub direction: Vec2,
+    pub speed: f32,
+    timer: Timer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlidePhase {
+    Dashing,
+    Recovering,
+}
+
+/// Counts down until a character can slide again.
+#[derive(Component, Reflect, Debug, Deref, DerefMut)]
+#[reflect(Component)]
+pub struct SlideCooldown(Timer);
+
+/// Start a slide in the direction of the input, or the way the character is facing.
+/// Characters that are already sliding or have a [`SlideCooldown`] shouldn't start one.
+pub fn start_slide(
+    commands: &mut Commands,
+    entity: Entity,
+    settings: &SlideSettings,
+    input: Vec2,
+    facing: Facing,
+) {
+    let direction = if input == Vec2::ZERO {
+        facing.as_vec2()
+    } else {
+        input.normalize()
+    };
+    commands.entity(entity).insert((
+        Sliding {
+            phase: SlidePhase::Dashing,
+            direction,
+            speed: settings.speed,
+            timer: Timer::from_seconds(settings.duration, TimerMode::Once),
+        },
+        SlideCooldown(Timer::from_seconds(settings.cooldown, TimerMode::Once)),
+    ));
+}
+
+impl Sliding {
+    /// Turn towards `input` and keep moving at the slide's speed.
+    pub fn steer(&mut self, velocity: &mut Vec2, input: Vec2, steering: f32, dt: f32) {
+        if input != Vec2::ZERO {
+            self.direction = self
+                .direction
+                .lerp(input, 1.0 - f32::exp(-steering * dt))
+                .try_normalize()
+                .unwrap_or(self.direction);
+        }
+        *velocity = self.direction * self.speed;
+    }
+}
+
+fn tick_slides(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut slide_q: Query<(Entity, &mut Sliding, &SlideSettings)>,
+    mut cooldown_q: Query<(Entity, &mut SlideCooldown)>,
+) {
+    for (entity, mut sliding, settings) in &mut slide_q {
+        sliding.timer.tick(time.delta());
+        if !sliding.timer.finished() {
+            continue;
+        }
+        match sliding.phase {
+            SlidePhase::Dashing => {
+                sliding.phase = SlidePhase::Recovering;
+                sliding.timer = Timer::from_seconds(settings.recovery, TimerMode::Once);
+            }
+            SlidePhase::Recovering => {
+                commands.entity(entity).remove::<Sliding>();
+            }
+        }
+    }
+    for (entity, mut cooldown) in &mut cooldown_q {
+        if cooldown.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<SlideCooldown>();
+        }
+    }
+}
+
+/// Dashing into a wall sends the character back the way it came, mirrored along the wall.
+fn bounce_off_walls(
+    collisions: Res<Collisions>,
+    layers_q: Query<&CollisionLayers>,
+    mut slide_q: Query<(
+        Entity,
+        &mut Sliding,
+        &mut LinearVelocity,
+        &Rotation,
+        &SlideSettings,
+    )>,
+) {
+    for (entity, mut sliding, mut velocity, rotation, settings) in &mut slide_q {
+        if sliding.phase != SlidePhase::Dashing {
+            continue;
+        }
+        for contacts in collisions.collisions_with_entity(entity) {
+            let (other, is_first) = if contacts.entity1 == entity {
+                (contacts.entity2, true)
+            } else {
+                (contacts.entity1, false)
+            };
+            let is_wall = layers_q
+                .get(other)
+                .is_ok_and(|layers| layers.memberships.has_all(PhysicsLayers::World));
+            if !is_wall {
+                continue;
+            }
+            for manifold in &contacts.manifolds {
+                // Points away from the wall.
+                let normal = if is_first {
+                    -manifold.global_normal1(rotation)
+                } else {
+                    -manifold.global_normal2(rotation)
+                };
+                if sliding.direction.dot(normal) < 0.0 {
+                    sliding.direction -= 2.0 * sliding.direction.dot(normal) * normal;
+                    sliding.speed *= settings.bounce;
+                    velocity.0 = sliding.direction * sliding.speed;
+                }
+            }
+        }
+    }
+}
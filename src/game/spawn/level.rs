@@ -1,8 +1,37 @@
 //! Spawn the main level by triggering other observers.
 
+use avian2d::{
+    collision::{Collider, CollisionLayers, Sensor},
+    dynamics::rigid_body::RigidBody,
+};
 use bevy::prelude::*;
 
-use super::{inter::SpawnInter, player::SpawnPlayer};
+use super::{inter::SpawnInter, npc::SpawnNpc, player::SpawnPlayer};
+use crate::{
+    game::{
+        assets::{CutsceneKey, DialogueKey},
+        audio::soundtrack::{MusicArea, PlaylistKey},
+        camera::YSorted,
+        chest::{Chest, ChestLid, Loot},
+        constants::TILE_SIZE,
+        cutscene::CutsceneZone,
+        drops::{Breakable, DropTable},
+        flags::Requirement,
+        ground::{GroundMaterial, Surface},
+        hazard::{Checkpoint, DeepWater, Pit},
+        keys::{Dungeon, KeyKind, KeyPickup, LockedDoor},
+        npc::{Route, Wander},
+        physics::PhysicsLayers,
+        puzzle::{tile_center, Pushable},
+        signal::{Bridge, Door, Emitter, Lever, PressurePlate, Receiver},
+    },
+    screen::Screen,
+};
+
+// There is no level art yet, so everything is drawn as a plain rectangle.
+const WALL_COLOR: Color = Color::srgb(0.35, 0.33, 0.3);
+const DOOR_COLOR: Color = Color::srgb(0.5, 0.35, 0.2);
+const CHASM_COLOR: Color = Color::srgb(0.05, 0.05, 0.08);
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_level);
@@ -16,4 +45,445 @@ fn spawn_level(_trigger: Trigger<SpawnLevel>, mut commands: Commands) {
     // but add things like walls etc. here.
     commands.trigger(SpawnPlayer);
     commands.trigger(SpawnInter);
+    commands.trigger(SpawnNpc {
+        name: "Villager".to_string(),
+        position: Vec2::new(60.0, 40.0),
+        tint: Color::srgb(0.7, 0.85, 1.0),
+        dialogue: Some(DialogueKey::Villager),
+        wander: Some(Wander::default()),
+        route: None,
+        shop: None,
+    });
+    commands.trigger(SpawnNpc {
+        name: "Guard".to_string(),
+        position: Vec2::new(-80.0, -60.0),
+        tint: Color::srgb(1.0, 0.75, 0.7),
+        dialogue: None,
+        wander: None,
+        route: Some(Route {
+            waypoints: vec![
+                Vec2::new(-80.0, -60.0),
+                Vec2::new(-80.0, 60.0),
+                Vec2::new(-140.0, 60.0),
+                Vec2::new(-140.0, -60.0),
+            ],
+            rest_secs: 1.5,
+        }),
+        shop: None,
+    });
+    commands.trigger(SpawnNpc {
+        name: "Shopkeeper".to_string(),
+        position: Vec2::new(-20.0, 100.0),
+        tint: Color::srgb(1.0, 0.95, 0.6),
+        dialogue: None,
+        wander: None,
+        route: None,
+        shop: Some("village".to_string()),
+    });
+    spawn_chest(
+        &mut commands,
+        vec![
+            (Loot::Rupees(20), 3.0),
+            (
+                Loot::Item {
+                    item: "bombs".to_string(),
+                    count: 3,
+                },
+                1.0,
+            ),
+        ],
+        "village_chest",
+        Vec2::new(100.0, -20.0),
+    );
+    // Grass to blow up for rupees. There is no grass art yet.
+    for position in [
+        Vec2::new(40.0, -70.0),
+        Vec2::new(52.0, -70.0),
+        Vec2::new(64.0, -70.0),
+        Vec2::new(46.0, -82.0),
+        Vec2::new(58.0, -82.0),
+    ] {
+        commands.spawn((
+            Name::new("Grass"),
+            Breakable,
+            DropTable {
+                drops: vec![(Loot::Rupees(1), 3.0), (Loot::Rupees(5), 1.0)],
+                nothing: 4.0,
+            },
+            Sensor,
+            Collider::rectangle(12.0, 12.0),
+            CollisionLayers::new(PhysicsLayers::World, PhysicsLayers::Weapon),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.3, 0.6, 0.25),
+                    custom_size: Some(Vec2::splat(12.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..default()
+            },
+            YSorted::default(),
+            StateScoped(Screen::Playing),
+        ));
+    }
+    spawn_shrine(&mut commands);
+    spawn_chasm(&mut commands);
+    commands.spawn((
+        GroundMaterial::Stone,
+        zone(
+            "Shrine Path",
+            Vec2::new(144.0, 0.0),
+            Vec2::new(112.0, 16.0),
+            Color::srgb(0.5, 0.48, 0.45),
+        ),
+    ));
+    commands.spawn((
+        GroundMaterial::Sand,
+        zone(
+            "Sand",
+            Vec2::new(-200.0, 40.0),
+            Vec2::new(48.0, 64.0),
+            Color::srgb(0.85, 0.78, 0.55),
+        ),
+    ));
+    commands.spawn((
+        DeepWater,
+        GroundMaterial::Water,
+        zone(
+            "Pond",
+            Vec2::new(-160.0, 150.0),
+            Vec2::new(64.0, 48.0),
+            Color::srgb(0.2, 0.35, 0.7),
+        ),
+    ));
+    commands.spawn((
+        Surface::ICE,
+        zone(
+            "Ice",
+            Vec2::new(100.0, 140.0),
+            Vec2::new(64.0, 48.0),
+            Color::srgb(0.75, 0.9, 1.0),
+        ),
+    ));
+    commands.spawn((
+        Surface::MUD,
+        zone(
+            "Mud",
+            Vec2::new(-200.0, -40.0),
+            Vec2::splat(48.0),
+            Color::srgb(0.4, 0.3, 0.2),
+        ),
+    ));
+    commands.spawn((
+        Surface::conveyor(Vec2::new(40.0, 0.0)),
+        zone(
+            "Conveyor",
+            Vec2::new(140.0, -100.0),
+            Vec2::new(64.0, 16.0),
+            Color::srgb(0.3, 0.3, 0.35),
+        ),
+    ));
+    // The intro plays once, right where the player starts.
+    commands.spawn((
+        Name::new("Intro Cutscene Zone"),
+        CutsceneZone::new(
+            CutsceneKey::Intro,
+            Some(Requirement::NotFlag("intro_seen".to_string())),
+        ),
+        Sensor,
+        Collider::circle(24.0),
+        CollisionLayers::new(PhysicsLayers::Zone, PhysicsLayers::Actor),
+        SpatialBundle::default(),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+/// A walled shrine east of the village. Its door opens while its pressure plate is held down,
+/// which the block next to it can be pushed onto.
+/// Inside lies a small key for the locked door to the treasure room behind it.
+fn spawn_shrine(commands: &mut Commands) {
+    for (center, size) in [
+        (Vec2::new(208.0, 44.0), Vec2::new(16.0, 56.0)),
+        (Vec2::new(208.0, -44.0), Vec2::new(16.0, 56.0)),
+        (Vec2::new(264.0, 80.0), Vec2::new(128.0, 16.0)),
+        (Vec2::new(264.0, -80.0), Vec2::new(128.0, 16.0)),
+        (Vec2::new(320.0, 44.0), Vec2::new(16.0, 56.0)),
+        (Vec2::new(320.0, -44.0), Vec2::new(16.0, 56.0)),
+        // The treasure room.
+        (Vec2::new(352.0, 32.0), Vec2::new(64.0, 16.0)),
+        (Vec2::new(352.0, -32.0), Vec2::new(64.0, 16.0)),
+        (Vec2::new(376.0, 0.0), Vec2::new(16.0, 48.0)),
+    ] {
+        commands.spawn(wall("Shrine Wall", center, size, WALL_COLOR));
+    }
+    commands.spawn((
+        Dungeon("shrine".to_string()),
+        MusicArea(PlaylistKey::Dungeon),
+        GroundMaterial::Stone,
+        zone(
+            "Shrine Floor",
+            Vec2::new(264.0, 0.0),
+            Vec2::new(96.0, 144.0),
+            Color::srgb(0.4, 0.4, 0.42),
+        ),
+    ));
+    commands
+        .spawn((
+            Emitter {
+                signal: "shrine_plate".to_string(),
+                active: false,
+            },
+            PressurePlate,
+            zone(
+                "Pressure Plate",
+                Vec2::new(176.0, 32.0),
+                Vec2::splat(14.0),
+                Color::srgb(0.55, 0.55, 0.6),
+            ),
+        ))
+        .insert(CollisionLayers::new(
+            PhysicsLayers::Zone,
+            [PhysicsLayers::Actor, PhysicsLayers::World],
+        ));
+    commands.spawn((
+        Name::new("Block"),
+        Pushable::default(),
+        RigidBody::Static,
+        Collider::rectangle(TILE_SIZE, TILE_SIZE),
+        CollisionLayers::new(
+            PhysicsLayers::World,
+            [PhysicsLayers::Actor, PhysicsLayers::Zone],
+        ),
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.45, 0.4, 0.35),
+                custom_size: Some(Vec2::splat(TILE_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(tile_center(IVec2::new(9, 2)).extend(0.0)),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+    commands.spawn((
+        Receiver {
+            inputs: vec!["shrine_plate".to_string()],
+            ..default()
+        },
+        Door,
+        wall(
+            "Shrine Door",
+            Vec2::new(208.0, 0.0),
+            Vec2::new(16.0, 32.0),
+            DOOR_COLOR,
+        ),
+    ));
+    // There is no key art yet.
+    commands.spawn((
+        Name::new("Small Key"),
+        KeyPickup {
+            dungeon: "shrine".to_string(),
+            kind: KeyKind::Small,
+            id: Some("shrine_key".to_string()),
+        },
+        Sensor,
+        Collider::rectangle(6.0, 10.0),
+        CollisionLayers::new(PhysicsLayers::Zone, PhysicsLayers::Actor),
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.95, 0.8, 0.2),
+                custom_size: Some(Vec2::new(6.0, 10.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(256.0, 40.0, 0.0),
+            ..default()
+        },
+        YSorted::default(),
+        StateScoped(Screen::Playing),
+    ));
+    commands
+        .spawn((
+            LockedDoor {
+                dungeon: "shrine".to_string(),
+                kind: KeyKind::Small,
+                id: Some("shrine_locked_door".to_string()),
+            },
+            wall(
+                "Locked Door",
+                Vec2::new(320.0, 0.0),
+                Vec2::new(16.0, 32.0),
+                Color::srgb(0.6, 0.5, 0.2),
+            ),
+        ))
+        .insert(CollisionLayers::new(
+            [PhysicsLayers::World, PhysicsLayers::Interactable],
+            [PhysicsLayers::Actor, PhysicsLayers::No],
+        ));
+    spawn_chest(
+        commands,
+        vec![(Loot::Rupees(50), 1.0)],
+        "shrine_chest",
+        Vec2::new(356.0, 0.0),
+    );
+}
+
+/// A chasm south of the village, crossed by a bridge that a lever extends,
+/// with a checkpoint on the far side.
+fn spawn_chasm(commands: &mut Commands) {
+    for center in [Vec2::new(-68.0, -160.0), Vec2::new(68.0, -160.0)] {
+        commands.spawn((
+            Pit,
+            zone("Chasm", center, Vec2::new(88.0, 32.0), CHASM_COLOR),
+        ));
+    }
+    // Only drawn, so the gap shows while the bridge is retracted.
+    commands.spawn((
+        Name::new("Chasm Under Bridge"),
+        SpriteBundle {
+            sprite: Sprite {
+                color: CHASM_COLOR,
+                custom_size: Some(Vec2::new(48.0, 32.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, -160.0, -1.1),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+    // Over the chasm, so it's a pit too while retracted.
+    commands.spawn((
+        Receiver {
+            inputs: vec!["chasm_lever".to_string()],
+            ..default()
+        },
+        Bridge,
+        GroundMaterial::Wood,
+        zone(
+            "Bridge",
+            Vec2::new(0.0, -160.0),
+            Vec2::new(48.0, 32.0),
+            Color::srgb(0.55, 0.4, 0.25),
+        ),
+    ));
+    commands.spawn((
+        Checkpoint,
+        zone(
+            "Checkpoint",
+            Vec2::new(0.0, -208.0),
+            Vec2::splat(16.0),
+            Color::srgb(0.4, 0.7, 0.9),
+        ),
+    ));
+    // There is no lever art yet.
+    commands.spawn((
+        Name::new("Lever"),
+        Emitter {
+            signal: "chasm_lever".to_string(),
+            active: false,
+        },
+        Lever,
+        RigidBody::Static,
+        Collider::rectangle(8.0, 8.0),
+        CollisionLayers::new(
+            [PhysicsLayers::World, PhysicsLayers::Interactable],
+            [PhysicsLayers::Actor, PhysicsLayers::No],
+        ),
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.7, 0.7, 0.75),
+                custom_size: Some(Vec2::splat(8.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(-40.0, -128.0, 0.0),
+            ..default()
+        },
+        YSorted::default(),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+/// A chest whose lid lifts off when it's opened. There is no chest art yet.
+fn spawn_chest(commands: &mut Commands, loot: Vec<(Loot, f32)>, id: &str, position: Vec2) {
+    commands
+        .spawn((
+            Name::new("Chest"),
+            Chest {
+                loot,
+                id: Some(id.to_string()),
+                ..default()
+            },
+            RigidBody::Static,
+            Collider::rectangle(16.0, 12.0),
+            CollisionLayers::new(
+                [PhysicsLayers::World, PhysicsLayers::Interactable],
+                [PhysicsLayers::Actor, PhysicsLayers::No],
+            ),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.6, 0.4, 0.2),
+                    custom_size: Some(Vec2::new(16.0, 12.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..default()
+            },
+            YSorted::default(),
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Chest Lid"),
+                ChestLid,
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::srgb(0.45, 0.3, 0.15),
+                        custom_size: Some(Vec2::new(16.0, 5.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 4.0, 0.1),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// A solid obstacle.
+fn wall(name: &'static str, center: Vec2, size: Vec2, color: Color) -> impl Bundle {
+    (
+        Name::new(name),
+        RigidBody::Static,
+        Collider::rectangle(size.x, size.y),
+        CollisionLayers::new(PhysicsLayers::World, PhysicsLayers::Actor),
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(center.extend(0.0)),
+            ..default()
+        },
+        YSorted::default(),
+        StateScoped(Screen::Playing),
+    )
+}
+
+/// An area on the ground that notices actors on it, drawn below everything else.
+fn zone(name: &'static str, center: Vec2, size: Vec2, color: Color) -> impl Bundle {
+    (
+        Name::new(name),
+        Sensor,
+        Collider::rectangle(size.x, size.y),
+        CollisionLayers::new(PhysicsLayers::Zone, PhysicsLayers::Actor),
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(center.extend(-1.0)),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    )
 }
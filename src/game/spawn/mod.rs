@@ -6,8 +6,9 @@ use bevy::prelude::*;
 
 pub mod inter;
 pub mod level;
+pub mod npc;
 pub mod player;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((inter::plugin, level::plugin, player::plugin));
+    app.add_plugins((inter::plugin, level::plugin, npc::plugin, player::plugin));
 }
@@ -0,0 +1,118 @@
+//! Spawn NPCs.
+
+use avian2d::collision::{Collider, CollisionLayers};
+use bevy::prelude::*;
+use bevy_spritesheet_animation::component::SpritesheetAnimation;
+
+use crate::{
+    game::{
+        animation::{state::AnimationParams, AnimationNames, AnimationSet},
+        assets::{AnimationKey, DialogueKey, HandleMap, ImageKey},
+        camera::YSorted,
+        dialogue::Talker,
+        facing::Facing,
+        ground::{Footing, OnSurface},
+        npc::{Npc, NpcSprite, Route, Wander},
+        physics::PhysicsLayers,
+        player::{player_animations, CharacterControllerBundle, FootstepSound, PlayerDir},
+        shop::Shopkeeper,
+    },
+    screen::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(spawn_npc);
+}
+
+/// Spawns an NPC. There is no NPC art yet, so they look like the player, tinted.
+#[derive(Event, Debug, Clone)]
+pub struct SpawnNpc {
+    pub name: String,
+    pub position: Vec2,
+    pub tint: Color,
+    pub dialogue: Option<DialogueKey>,
+    pub wander: Option<Wander>,
+    pub route: Option<Route>,
+    /// Name of the shop in the [`ShopCatalog`](crate::game::shop::ShopCatalog) they sell from.
+    pub shop: Option<String>,
+}
+
+fn spawn_npc(
+    trigger: Trigger<SpawnNpc>,
+    mut commands: Commands,
+    image_handles: Res<HandleMap<ImageKey>>,
+    animation_handles: Res<HandleMap<AnimationKey>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    animation_names: Res<AnimationNames>,
+) {
+    let (Some(animation_set), Some(idle_anim_id)) = (
+        animation_sets.get(&animation_handles[&AnimationKey::Player]),
+        animation_names.get("down_idle_player"),
+    ) else {
+        error!("NPC animations are not loaded");
+        return;
+    };
+    let npc = trigger.event();
+
+    let texture = animation_set
+        .texture
+        .clone()
+        .unwrap_or_else(|| image_handles[&ImageKey::Player].clone_weak());
+    let layout = animation_set.layout.clone();
+    let mut entity = commands.spawn((
+        Name::new(npc.name.clone()),
+        Npc,
+        StateScoped(Screen::Playing),
+        CharacterControllerBundle::new(Collider::circle(7.5)).with_movement(40.0, 8.0, 10.0),
+        // Also interactable, so the player can talk to them.
+        CollisionLayers::new(
+            [PhysicsLayers::Actor, PhysicsLayers::Interactable],
+            [
+                PhysicsLayers::World,
+                PhysicsLayers::Low,
+                PhysicsLayers::Actor,
+                PhysicsLayers::No,
+            ],
+        ),
+        SpatialBundle::from_transform(Transform::from_translation(npc.position.extend(0.0))),
+        PlayerDir::default(),
+        Facing::default(),
+        FootstepSound::default().with_interval(20.0),
+        Footing::default(),
+        OnSurface::default(),
+    ));
+    entity.with_children(|children| {
+        children.spawn((
+            Name::new("NPC Sprite"),
+            TextureAtlas {
+                layout,
+                ..default()
+            },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: npc.tint,
+                    ..default()
+                },
+                texture,
+                ..default()
+            },
+            YSorted::default(),
+            SpritesheetAnimation::from_id(idle_anim_id),
+            player_animations(),
+            AnimationParams::default(),
+            NpcSprite,
+        ));
+    });
+    if let Some(dialogue) = npc.dialogue {
+        entity.insert(Talker(dialogue));
+    }
+    if let Some(wander) = npc.wander {
+        entity.insert(wander);
+    }
+    if let Some(route) = npc.route.clone() {
+        entity.insert(route);
+    }
+    if let Some(shop) = npc.shop.clone() {
+        entity.insert(Shopkeeper(shop));
+    }
+}
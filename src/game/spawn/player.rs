@@ -2,21 +2,26 @@
 
 use avian2d::collision::{Collider, CollisionLayers, Sensor};
 use bevy::prelude::*;
-use bevy_spritesheet_animation::{
-    animation::{AnimationDuration, AnimationRepeat},
-    component::SpritesheetAnimation,
-    library::SpritesheetLibrary,
-    spritesheet::Spritesheet,
-};
+use bevy_spritesheet_animation::component::SpritesheetAnimation;
 
 #[cfg(feature = "dev")]
 use crate::dev_tools::FpsTrack;
 use crate::{
     game::{
-        assets::{HandleMap, ImageKey},
+        animation::{state::AnimationParams, AnimationNames, AnimationSet},
+        assets::{AnimationKey, HandleMap, ImageKey},
         camera::YSorted,
+        facing::Facing,
+        ground::{Footing, OnSurface},
+        hazard::RespawnPoint,
+        height::{Height, JumpImpulse},
         physics::PhysicsLayers,
-        player::{CharacterControllerBundle, FootstepSound, Interacter, PlayerDir, PlayerSprite},
+        player::{
+            player_animations, CharacterControllerBundle, FootstepSound, Interacter, PlayerDir,
+            PlayerSprite,
+        },
+        save::SaveData,
+        slide::SlideSettings,
     },
     screen::Screen,
 };
@@ -38,82 +43,25 @@ fn spawn_player(
     _trigger: Trigger<SpawnPlayer>,
     mut commands: Commands,
     image_handles: Res<HandleMap<ImageKey>>,
-    mut library: ResMut<SpritesheetLibrary>,
-    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    animation_handles: Res<HandleMap<AnimationKey>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    animation_names: Res<AnimationNames>,
+    respawn_point: Res<RespawnPoint>,
+    save: Res<SaveData>,
 ) {
-    let walk_clip_id = library.new_clip(|clip| {
-        clip.push_frame_indices(Spritesheet::new(5, 9).row_partial(0, 0..=3));
-    });
-
-    let walk_anim_id = library.new_animation(|animation| {
-        animation
-            .add_stage(walk_clip_id.into())
-            .set_repeat(AnimationRepeat::Loop);
-    });
-    if let Err(e) = library.name_animation(walk_anim_id, "horizontal_walk_player") {
-        warn!("error naming anim: {e:?}");
-    };
-    let walk_clip_id = library.new_clip(|clip| {
-        clip.push_frame_indices(Spritesheet::new(5, 9).row_partial(3, 0..=0));
-    });
-
-    let walk_anim_id = library.new_animation(|animation| {
-        animation
-            .add_stage(walk_clip_id.into())
-            .set_repeat(AnimationRepeat::Loop);
-    });
-    if let Err(e) = library.name_animation(walk_anim_id, "horizontal_slide_player") {
-        warn!("error naming anim: {e:?}");
-    };
-    let walk_clip_id = library.new_clip(|clip| {
-        clip.push_frame_indices(Spritesheet::new(5, 9).row_partial(6, 0..=0));
-    });
-
-    let walk_anim_id = library.new_animation(|animation| {
-        animation
-            .add_stage(walk_clip_id.into())
-            .set_repeat(AnimationRepeat::Loop)
-            .set_duration(AnimationDuration::PerCycle(3000));
-    });
-    if let Err(e) = library.name_animation(walk_anim_id, "vertical_slide_player") {
-        warn!("error naming anim: {e:?}");
+    let (Some(animation_set), Some(idle_anim_id)) = (
+        animation_sets.get(&animation_handles[&AnimationKey::Player]),
+        animation_names.get("down_idle_player"),
+    ) else {
+        error!("player animations are not loaded");
+        return;
     };
-    let walk_clip_id = library.new_clip(|clip| {
-        clip.push_frame_indices(Spritesheet::new(5, 9).row_partial(1, 0..3));
-    });
 
-    let walk_anim_id = library.new_animation(|animation| {
-        animation
-            .add_stage(walk_clip_id.into())
-            .set_repeat(AnimationRepeat::Loop);
-    });
-    if let Err(e) = library.name_animation(walk_anim_id, "vertical_walk_player") {
-        warn!("error naming anim: {e:?}");
-    };
-    let idle_clip_id = library.new_clip(|clip| {
-        clip.push_frame_indices(Spritesheet::new(5, 9).row_partial(7, 0..=3));
-    });
-
-    let idle_anim_id = library.new_animation(|anim| {
-        anim.add_stage(idle_clip_id.into())
-            .set_repeat(AnimationRepeat::Loop)
-            .set_duration(AnimationDuration::PerFrame(2500));
-    });
-
-    if let Err(e) = library.name_animation(idle_anim_id, "idle_player") {
-        warn!("error naming anim: {e:?}");
-    };
-    // Spawn a sprite using Bevy's built-in SpriteSheetBundle
-
-    let texture = image_handles[&ImageKey::Player].clone_weak();
-
-    let layout = atlas_layouts.add(TextureAtlasLayout::from_grid(
-        UVec2::new(19, 21),
-        5,
-        9,
-        None,
-        None,
-    ));
+    let texture = animation_set
+        .texture
+        .clone()
+        .unwrap_or_else(|| image_handles[&ImageKey::Player].clone_weak());
+    let layout = animation_set.layout.clone();
     commands.spawn((
         Name::new("Player Sprite"),
         TextureAtlas {
@@ -126,6 +74,8 @@ fn spawn_player(
         },
         YSorted::default(),
         SpritesheetAnimation::from_id(idle_anim_id),
+        player_animations(),
+        AnimationParams::default(),
         PlayerSprite::default(),
         StateScoped(Screen::Playing),
     ));
@@ -148,11 +98,23 @@ fn spawn_player(
             CharacterControllerBundle::new(Collider::circle(7.5)).with_movement(500.0, 1.0, 3.0),
             CollisionLayers::new(
                 PhysicsLayers::Actor,
-                [PhysicsLayers::World, PhysicsLayers::Actor],
+                [
+                    PhysicsLayers::World,
+                    PhysicsLayers::Low,
+                    PhysicsLayers::Actor,
+                    PhysicsLayers::Zone,
+                ],
             ),
-            SpatialBundle::default(),
+            SpatialBundle::from_transform(Transform::from_translation(respawn_point.extend(0.0))),
             PlayerDir::default(),
+            Facing::default(),
+            Height::default(),
+            JumpImpulse::default(),
+            SlideSettings::default(),
             FootstepSound::default().with_interval(20.0),
+            Footing::default(),
+            OnSurface::default(),
+            save.inventory.clone(),
         ))
         .id();
     #[cfg(feature = "dev")]
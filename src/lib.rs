@@ -2,6 +2,7 @@
 mod dev_tools;
 mod game;
 mod screen;
+mod tween;
 mod ui;
 pub mod utils;
 
@@ -13,7 +14,7 @@ use bevy::{
 use bevy_framepace::{FramepacePlugin, FramepaceSettings, Limiter};
 #[cfg(feature = "dev")]
 use dev_tools::FpsTrack;
-use game::camera::PrimaryCamera;
+use game::{audio::sfx::LISTENER_EAR_GAP, camera::PrimaryCamera};
 
 pub struct AppPlugin;
 
@@ -60,7 +61,7 @@ impl Plugin for AppPlugin {
         app.add_plugins(FramepacePlugin);
 
         // Add other plugins.
-        app.add_plugins((game::plugin, screen::plugin, ui::plugin));
+        app.add_plugins((game::plugin, screen::plugin, tween::plugin, ui::plugin));
 
         // Enable dev tools for dev builds.
         #[cfg(feature = "dev")]
@@ -94,6 +95,8 @@ fn spawn_camera(mut commands: Commands) {
         // for debugging. So it's good to have this here for future-proofing.
         PrimaryCamera(Vec2::ZERO, Vec3::splat(1.0), true),
         IsDefaultUiCamera,
+        // Positional sound effects are heard relative to the camera.
+        SpatialListener::new(LISTENER_EAR_GAP),
     ));
 }
 fn setup_framepace(mut pace: ResMut<FramepaceSettings>) {
@@ -0,0 +1,302 @@
+//! The inventory sub-screen, opened on top of the game.
+//! Items are picked with the movement keys or the gamepad's d-pad
+//! and put in an equip slot with that slot's input.
+
+use bevy::prelude::*;
+
+use super::{Menu, Screen};
+use crate::{
+    game::{
+        assets::ItemCatalogHandle,
+        constants::{DOWN, INVENTORY, LEFT, RIGHT, UP},
+        inventory::{Inventory, ItemCatalog, EQUIP_INPUTS, EQUIP_SLOTS},
+        pause::{gameplay_running, GameplayPause, PauseReason},
+        spawn::player::Player,
+    },
+    ui::prelude::*,
+    utils::gamepad_just_pressed,
+    AppSet,
+};
+
+/// Items per row of the item grid.
+const COLUMNS: usize = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Inventory), enter_inventory);
+    app.add_systems(OnExit(Menu::Inventory), exit_inventory);
+    app.add_systems(
+        Update,
+        (
+            open_inventory
+                .run_if(in_state(Menu::None).and_then(gameplay_running))
+                .in_set(AppSet::RecordInput),
+            (
+                (
+                    close_inventory,
+                    move_cursor,
+                    select_clicked_item,
+                    equip_selected,
+                ),
+                update_inventory_screen,
+            )
+                .chain()
+                .run_if(in_state(Menu::Inventory))
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// The held items, in catalog order, and which one is selected.
+#[derive(Resource, Debug, Default)]
+struct InventoryCursor {
+    items: Vec<String>,
+    selected: usize,
+}
+
+/// A button for the held item at this index of [`InventoryCursor::items`].
+#[derive(Component, Debug)]
+struct ItemButton(usize);
+
+/// Marker for the label describing the selected item.
+#[derive(Component)]
+struct ItemDescription;
+
+/// Marker for the label listing the equipped items.
+#[derive(Component)]
+struct EquippedItems;
+
+fn open_inventory(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    if kb.any_just_pressed(INVENTORY.iter().copied())
+        || gamepad_just_pressed(&gamepads, &buttons, GamepadButtonType::Start)
+    {
+        next_menu.set(Menu::Inventory);
+    }
+}
+
+fn enter_inventory(
+    mut commands: Commands,
+    mut pause: ResMut<GameplayPause>,
+    catalog_handle: Res<ItemCatalogHandle>,
+    catalogs: Res<Assets<ItemCatalog>>,
+    inventory_q: Query<&Inventory, With<Player>>,
+) {
+    pause.add(PauseReason::Menu);
+    let inventory = inventory_q.get_single().ok();
+    let held: Vec<_> = catalogs
+        .get(&catalog_handle.0)
+        .into_iter()
+        .flat_map(ItemCatalog::iter)
+        .filter_map(|item| {
+            let count = inventory.map_or(0, |inventory| inventory.count(&item.id));
+            (count > 0).then_some((item, count))
+        })
+        .collect();
+
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(Menu::Inventory),
+            BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        ))
+        .with_children(|children| {
+            children.header("Inventory");
+            children
+                .spawn((
+                    Name::new("Item Grid"),
+                    NodeBundle {
+                        style: Style {
+                            display: Display::Grid,
+                            grid_template_columns: RepeatedGridTrack::auto(COLUMNS as u16),
+                            column_gap: Val::Px(10.0),
+                            row_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    for (index, (item, count)) in held.iter().enumerate() {
+                        let text = match item.max_stack {
+                            1 => item.name.clone(),
+                            _ => format!("{} x{count}", item.name),
+                        };
+                        children.button(text).insert(ItemButton(index));
+                    }
+                });
+            if held.is_empty() {
+                children.label("You don't have any items yet.");
+            }
+            children.label("").insert(ItemDescription);
+            children.label("").insert(EquippedItems);
+            children.label("J / L: equip    Tab: close");
+        });
+
+    commands.insert_resource(InventoryCursor {
+        items: held.iter().map(|(item, _)| item.id.clone()).collect(),
+        selected: 0,
+    });
+}
+
+fn exit_inventory(mut commands: Commands, mut pause: ResMut<GameplayPause>) {
+    pause.remove(PauseReason::Menu);
+    commands.remove_resource::<InventoryCursor>();
+}
+
+fn close_inventory(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    let pressed = |button| gamepad_just_pressed(&gamepads, &buttons, button);
+    if kb.any_just_pressed(INVENTORY.iter().copied().chain([KeyCode::Escape]))
+        || pressed(GamepadButtonType::Start)
+        || pressed(GamepadButtonType::East)
+    {
+        next_menu.set(Menu::None);
+    }
+}
+
+fn move_cursor(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut cursor: ResMut<InventoryCursor>,
+) {
+    let pressed = |keys: &[KeyCode], button| {
+        kb.any_just_pressed(keys.iter().copied())
+            || gamepad_just_pressed(&gamepads, &buttons, button)
+    };
+    let step: isize = if pressed(LEFT, GamepadButtonType::DPadLeft) {
+        -1
+    } else if pressed(RIGHT, GamepadButtonType::DPadRight) {
+        1
+    } else if pressed(UP, GamepadButtonType::DPadUp) {
+        -(COLUMNS as isize)
+    } else if pressed(DOWN, GamepadButtonType::DPadDown) {
+        COLUMNS as isize
+    } else {
+        return;
+    };
+    let target = cursor.selected as isize + step;
+    if (0..cursor.items.len() as isize).contains(&target) {
+        cursor.selected = target as usize;
+    }
+}
+
+fn select_clicked_item(
+    mut cursor: ResMut<InventoryCursor>,
+    mut button_query: InteractionQuery<&ItemButton>,
+) {
+    for (interaction, button) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            cursor.selected = button.0;
+        }
+    }
+}
+
+fn equip_selected(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    cursor: Res<InventoryCursor>,
+    catalog_handle: Res<ItemCatalogHandle>,
+    catalogs: Res<Assets<ItemCatalog>>,
+    mut inventory_q: Query<&mut Inventory, With<Player>>,
+) {
+    let Some(slot) = EQUIP_INPUTS.into_iter().position(|(keys, button)| {
+        kb.any_just_pressed(keys.iter().copied())
+            || gamepad_just_pressed(&gamepads, &buttons, button)
+    }) else {
+        return;
+    };
+    let Some(id) = cursor.items.get(cursor.selected) else {
+        return;
+    };
+    let equippable = catalogs
+        .get(&catalog_handle.0)
+        .and_then(|catalog| catalog.get(id))
+        .is_some_and(|item| item.equippable);
+    if !equippable {
+        return;
+    }
+    for mut inventory in &mut inventory_q {
+        inventory.equip(slot, id);
+    }
+}
+
+fn update_inventory_screen(
+    cursor: Res<InventoryCursor>,
+    catalog_handle: Res<ItemCatalogHandle>,
+    catalogs: Res<Assets<ItemCatalog>>,
+    inventory_q: Query<Ref<Inventory>, With<Player>>,
+    mut button_q: Query<(
+        &ItemButton,
+        &Interaction,
+        &mut InteractionPalette,
+        &mut BackgroundColor,
+    )>,
+    description_q: Query<&Children, With<ItemDescription>>,
+    equipped_q: Query<&Children, With<EquippedItems>>,
+    mut text_q: Query<&mut Text>,
+) {
+    let inventory = inventory_q.get_single().ok();
+    let inventory_changed = inventory
+        .as_ref()
+        .is_some_and(|inventory| inventory.is_changed());
+    if !cursor.is_changed() && !inventory_changed {
+        return;
+    }
+    let catalog = catalogs.get(&catalog_handle.0);
+    let name_of = |id: &str| {
+        catalog
+            .and_then(|catalog| catalog.get(id))
+            .map_or(id.to_string(), |item| item.name.clone())
+    };
+
+    for (button, interaction, mut palette, mut background) in &mut button_q {
+        palette.none = if button.0 == cursor.selected {
+            ui_palette::BUTTON_HOVERED_BACKGROUND
+        } else {
+            ui_palette::NODE_BACKGROUND
+        };
+        if matches!(interaction, Interaction::None) {
+            *background = palette.none.into();
+        }
+    }
+
+    let description = cursor
+        .items
+        .get(cursor.selected)
+        .and_then(|id| catalog?.get(id))
+        .map(|item| item.description.clone())
+        .unwrap_or_default();
+    let slot_keys = ["J", "L"];
+    let equipped_text = (0..EQUIP_SLOTS)
+        .map(|slot| {
+            let item = inventory
+                .as_ref()
+                .and_then(|inventory| inventory.equipped[slot].as_deref())
+                .map_or("-".to_string(), name_of);
+            format!("{}: {item}", slot_keys[slot])
+        })
+        .collect::<Vec<_>>()
+        .join("    ");
+
+    let descriptions = description_q
+        .iter()
+        .map(|children| (children, &description));
+    let equipped = equipped_q.iter().map(|children| (children, &equipped_text));
+    for (children, value) in descriptions.chain(equipped) {
+        let mut iter = text_q.iter_many_mut(children.iter());
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value.clone_from(value);
+        }
+    }
+}
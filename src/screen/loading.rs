@@ -1,40 +1,276 @@
 //! A loading screen during which game assets are loaded.
 //! This reduces stuttering, especially for audio on WASM.
 
-use bevy::prelude::*;
+use std::fmt::Debug;
+
+use bevy::{
+    asset::{AssetPath, LoadState},
+    prelude::*,
+};
 
 use super::Screen;
 use crate::{
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    game::assets::{
+        AnimationKey, AssetKey, CutsceneKey, DialogueKey, HandleMap, ImageKey, ItemCatalogHandle,
+        ManifestHandle, QuestCatalogHandle, RetryFailedAssets, SfxKey, ShopCatalogHandle,
+        SoundtrackKey,
+    },
     ui::prelude::*,
+    AppSet,
 };
 
+/// The loading screen stays up at least this long so it doesn't just flash by.
+const LOADING_MIN_DURATION_SECS: f32 = 0.8;
+
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Loading), enter_loading);
+    app.add_systems(OnExit(Screen::Loading), exit_loading);
+
+    app.register_type::<LoadingTimer>();
+    app.register_type::<LoadingAction>();
     app.add_systems(
         Update,
-        continue_to_title.run_if(in_state(Screen::Loading).and_then(all_assets_loaded)),
+        (
+            tick_loading_timer.in_set(AppSet::TickTimers),
+            (
+                update_loading_progress,
+                update_loading_screen,
+                handle_loading_action,
+            )
+                .chain()
+                .in_set(AppSet::Update),
+            continue_to_title
+                .run_if(all_assets_loaded)
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Loading)),
     );
 }
 
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LoadingAction {
+    Retry,
+    Continue,
+}
+
+/// Marker for the label showing how many assets are loaded.
+#[derive(Component)]
+struct LoadingStatus;
+
+/// Marker for the container listing failed assets and the retry/continue buttons.
+#[derive(Component)]
+struct FailedAssetList;
+
+/// How far along loading is, across every [`HandleMap`] and the data catalogs.
+#[derive(Resource, Debug, Default, PartialEq)]
+struct LoadingProgress {
+    total: usize,
+    /// Assets that finished loading, successfully or not.
+    done: usize,
+    /// Description and path of every asset that failed to load.
+    failed: Vec<(String, AssetPath<'static>)>,
+}
+
+impl LoadingProgress {
+    fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+
+    fn is_settled(&self) -> bool {
+        self.total > 0 && self.done == self.total
+    }
+
+    fn track(&mut self, name: String, state: LoadState, path: Option<&AssetPath<'static>>) {
+        self.total += 1;
+        match state {
+            LoadState::Loaded => self.done += 1,
+            LoadState::Failed(_) => {
+                self.done += 1;
+                if let Some(path) = path {
+                    self.failed.push((name, path.clone()));
+                }
+            }
+            LoadState::NotLoaded | LoadState::Loading => {}
+        }
+    }
+
+    fn track_map<K: AssetKey + Debug>(&mut self, asset_server: &AssetServer, map: &HandleMap<K>) {
+        for (key, handle) in map.iter() {
+            self.track(
+                format!("{key:?}"),
+                asset_server.load_state(handle.id()),
+                handle.path(),
+            );
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+struct LoadingTimer(Timer);
+
+impl Default for LoadingTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            LOADING_MIN_DURATION_SECS,
+            TimerMode::Once,
+        ))
+    }
+}
+
 fn enter_loading(mut commands: Commands) {
+    commands.init_resource::<LoadingTimer>();
+    commands.init_resource::<LoadingProgress>();
     commands
         .ui_root()
         .insert(StateScoped(Screen::Loading))
         .with_children(|children| {
-            children.label("Loading...");
+            children.label("Loading...").insert(LoadingStatus);
+            children.progress_bar();
+            children.spawn((
+                Name::new("Failed Assets"),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                FailedAssetList,
+            ));
         });
 }
 
-fn all_assets_loaded(
+fn exit_loading(mut commands: Commands) {
+    commands.remove_resource::<LoadingTimer>();
+    commands.remove_resource::<LoadingProgress>();
+}
+
+fn tick_loading_timer(time: Res<Time>, mut timer: ResMut<LoadingTimer>) {
+    timer.0.tick(time.delta());
+}
+
+fn update_loading_progress(
     asset_server: Res<AssetServer>,
+    manifest: Res<ManifestHandle>,
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
-) -> bool {
-    image_handles.all_loaded(&asset_server)
-        && sfx_handles.all_loaded(&asset_server)
-        && soundtrack_handles.all_loaded(&asset_server)
+    animation_handles: Res<HandleMap<AnimationKey>>,
+    dialogue_handles: Res<HandleMap<DialogueKey>>,
+    cutscene_handles: Res<HandleMap<CutsceneKey>>,
+    item_catalog: Res<ItemCatalogHandle>,
+    shop_catalog: Res<ShopCatalogHandle>,
+    quest_catalog: Res<QuestCatalogHandle>,
+    mut progress: ResMut<LoadingProgress>,
+) {
+    let mut next = LoadingProgress::default();
+    if !manifest.is_applied() {
+        // The handle maps are empty until the manifest has been applied,
+        // so a loaded but not yet applied manifest still counts as loading.
+        let state = match asset_server.load_state(manifest.handle().id()) {
+            LoadState::Loaded => LoadState::Loading,
+            state => state,
+        };
+        next.track(
+            "asset manifest".to_string(),
+            state,
+            manifest.handle().path(),
+        );
+    } else {
+        next.track_map(&asset_server, &image_handles);
+        next.track_map(&asset_server, &sfx_handles);
+        next.track_map(&asset_server, &soundtrack_handles);
+        next.track_map(&asset_server, &animation_handles);
+        next.track_map(&asset_server, &dialogue_handles);
+        next.track_map(&asset_server, &cutscene_handles);
+        next.track(
+            "item catalog".to_string(),
+            asset_server.load_state(item_catalog.0.id()),
+            item_catalog.0.path(),
+        );
+        next.track(
+            "shop catalog".to_string(),
+            asset_server.load_state(shop_catalog.0.id()),
+            shop_catalog.0.path(),
+        );
+        next.track(
+            "quest catalog".to_string(),
+            asset_server.load_state(quest_catalog.0.id()),
+            quest_catalog.0.path(),
+        );
+    }
+    if *progress != next {
+        *progress = next;
+    }
+}
+
+fn update_loading_screen(
+    mut commands: Commands,
+    progress: Res<LoadingProgress>,
+    manifest: Res<ManifestHandle>,
+    status_query: Query<&Children, With<LoadingStatus>>,
+    mut text_query: Query<&mut Text>,
+    mut bar_query: Query<&mut ProgressBar>,
+    list_query: Query<Entity, With<FailedAssetList>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+    for children in &status_query {
+        let mut iter = text_query.iter_many_mut(children.iter());
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = format!("Loading... {}/{}", progress.done, progress.total);
+        }
+    }
+    for mut bar in &mut bar_query {
+        bar.0 = progress.fraction();
+    }
+
+    let Ok(list) = list_query.get_single() else {
+        return;
+    };
+    commands.entity(list).despawn_descendants();
+    if !progress.is_settled() || progress.failed.is_empty() {
+        return;
+    }
+    commands.entity(list).with_children(|children| {
+        children.header("Failed to load");
+        for (name, path) in &progress.failed {
+            children.label(format!("{name}: {path}"));
+        }
+        children.button("Retry").insert(LoadingAction::Retry);
+        // Without the manifest there's nothing the game can run with.
+        if manifest.is_applied() {
+            children.button("Continue").insert(LoadingAction::Continue);
+        }
+    });
+}
+
+fn handle_loading_action(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&LoadingAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                LoadingAction::Retry => commands.trigger(RetryFailedAssets),
+                LoadingAction::Continue => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
+
+fn all_assets_loaded(progress: Res<LoadingProgress>, timer: Res<LoadingTimer>) -> bool {
+    timer.0.finished() && progress.is_settled() && progress.failed.is_empty()
 }
 
 fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
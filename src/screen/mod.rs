@@ -1,16 +1,22 @@
 //! The game's main screen states and transitions between them.
 
 mod credits;
+mod inventory;
 mod loading;
 mod playing;
+mod quest_log;
+mod shop;
 mod splash;
 mod title;
+mod transition;
 
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.enable_state_scoped_entities::<Screen>();
+    app.add_sub_state::<Menu>();
+    app.enable_state_scoped_entities::<Menu>();
 
     app.add_plugins((
         splash::plugin,
@@ -18,6 +24,10 @@ pub(super) fn plugin(app: &mut App) {
         title::plugin,
         credits::plugin,
         playing::plugin,
+        inventory::plugin,
+        shop::plugin,
+        quest_log::plugin,
+        transition::plugin,
     ));
 }
 
@@ -31,3 +41,14 @@ pub enum Screen {
     Credits,
     Playing,
 }
+
+/// Menus that open on top of [`Screen::Playing`].
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Default)]
+#[source(Screen = Screen::Playing)]
+pub enum Menu {
+    #[default]
+    None,
+    Inventory,
+    Shop,
+    QuestLog,
+}
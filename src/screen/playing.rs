@@ -1,34 +1,41 @@
 //! The screen state for the main game loop.
 
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
-use rand::Rng;
 
-use super::Screen;
-use crate::game::{
-    assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack, spawn::level::SpawnLevel,
+use super::{Menu, Screen};
+use crate::{
+    game::{
+        audio::soundtrack::{PlaySoundtrack, PlaylistKey},
+        cutscene::ActiveCutscene,
+        dialogue::ActiveDialogue,
+        spawn::level::SpawnLevel,
+    },
+    AppSet,
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Playing), enter_playing);
     app.add_systems(OnExit(Screen::Playing), exit_playing);
 
+    // Escape skips cutscenes instead. This runs before they do, so the same press
+    // can't also skip one and then leave the game. Leaving mid-conversation would
+    // drop the dialogue's pause and choices half done.
     app.add_systems(
         Update,
         return_to_title_screen
-            .run_if(in_state(Screen::Playing).and_then(input_just_pressed(KeyCode::Escape))),
+            .run_if(
+                in_state(Menu::None)
+                    .and_then(not(resource_exists::<ActiveCutscene>))
+                    .and_then(not(resource_exists::<ActiveDialogue>))
+                    .and_then(input_just_pressed(KeyCode::Escape)),
+            )
+            .in_set(AppSet::RecordInput),
     );
 }
 
 fn enter_playing(mut commands: Commands) {
     commands.trigger(SpawnLevel);
-    let key = match rand::thread_rng().gen_range(0..=3) {
-        0 => SoundtrackKey::GoingIn,
-        1 => SoundtrackKey::Worldwid3,
-        2 => SoundtrackKey::BigM,
-        3 => SoundtrackKey::Izo,
-        _ => unimplemented!(),
-    };
-    commands.trigger(PlaySoundtrack::Key(key));
+    commands.trigger(PlaySoundtrack::Playlist(PlaylistKey::Game));
 }
 
 fn exit_playing(mut commands: Commands) {
@@ -0,0 +1,121 @@
+//! The quest log sub-screen, opened on top of the game.
+//! Lists every started quest with its current stage and objectives, unfinished quests first.
+
+use bevy::prelude::*;
+
+use super::{Menu, Screen};
+use crate::{
+    game::{
+        assets::QuestCatalogHandle,
+        constants::QUEST_LOG,
+        pause::{gameplay_running, GameplayPause, PauseReason},
+        quest::QuestCatalog,
+        save::SaveData,
+    },
+    ui::prelude::*,
+    utils::gamepad_just_pressed,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::QuestLog), enter_quest_log);
+    app.add_systems(OnExit(Menu::QuestLog), exit_quest_log);
+    app.add_systems(
+        Update,
+        (
+            open_quest_log
+                .run_if(in_state(Menu::None).and_then(gameplay_running))
+                .in_set(AppSet::RecordInput),
+            close_quest_log
+                .run_if(in_state(Menu::QuestLog))
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+fn open_quest_log(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    if kb.any_just_pressed(QUEST_LOG.iter().copied())
+        || gamepad_just_pressed(&gamepads, &buttons, GamepadButtonType::Select)
+    {
+        next_menu.set(Menu::QuestLog);
+    }
+}
+
+fn close_quest_log(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    let pressed = |button| gamepad_just_pressed(&gamepads, &buttons, button);
+    if kb.any_just_pressed(QUEST_LOG.iter().copied().chain([KeyCode::Escape]))
+        || pressed(GamepadButtonType::Select)
+        || pressed(GamepadButtonType::East)
+    {
+        next_menu.set(Menu::None);
+    }
+}
+
+fn enter_quest_log(
+    mut commands: Commands,
+    mut pause: ResMut<GameplayPause>,
+    save: Res<SaveData>,
+    catalog_handle: Res<QuestCatalogHandle>,
+    catalogs: Res<Assets<QuestCatalog>>,
+) {
+    pause.add(PauseReason::Menu);
+    let mut started: Vec<_> = catalogs
+        .get(&catalog_handle.0)
+        .into_iter()
+        .flat_map(QuestCatalog::iter)
+        .filter_map(|quest| Some((quest, save.quests.get(&quest.id)?)))
+        .collect();
+    // Stable, so quests keep their catalog order within each group.
+    started.sort_by_key(|(_, progress)| progress.complete);
+
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(Menu::QuestLog),
+            BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        ))
+        .with_children(|children| {
+            children.header("Quests");
+            for (quest, progress) in &started {
+                if progress.complete {
+                    children.label(format!("{} (done)", quest.name));
+                    continue;
+                }
+                children.label(quest.name.clone());
+                if !quest.description.is_empty() {
+                    children.label(quest.description.clone());
+                }
+                let Some(stage) = quest.stages.get(progress.stage) else {
+                    continue;
+                };
+                children.label(stage.description.clone());
+                for (index, objective) in stage.objectives.iter().enumerate() {
+                    let check = if progress.objectives.contains(&index) {
+                        "x"
+                    } else {
+                        " "
+                    };
+                    children.label(format!("[{check}] {}", objective.description));
+                }
+            }
+            if started.is_empty() {
+                children.label("You haven't started any quests yet.");
+            }
+            children.label("Q: close");
+        });
+}
+
+fn exit_quest_log(mut pause: ResMut<GameplayPause>) {
+    pause.remove(PauseReason::Menu);
+}
@@ -0,0 +1,227 @@
+//! The shop sub-screen, opened by talking to a shopkeeper.
+//! Entries are picked with the movement keys or the gamepad's d-pad and bought with
+//! the interact key, or clicked.
+
+use bevy::prelude::*;
+
+use super::{Menu, Screen};
+use crate::{
+    game::{
+        assets::{ItemCatalogHandle, ShopCatalogHandle},
+        constants::{DOWN, INVENTORY, UP},
+        inventory::{Inventory, ItemCatalog},
+        pause::{GameplayPause, PauseReason},
+        save::SaveData,
+        shop::{buy, OpenShop, ShopCatalog, ShopEntry},
+        spawn::player::Player,
+    },
+    ui::prelude::*,
+    utils::gamepad_just_pressed,
+    AppSet,
+};
+
+/// Keys that buy the selected entry.
+const BUY: [KeyCode; 3] = [KeyCode::KeyE, KeyCode::Space, KeyCode::Enter];
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Shop), enter_shop);
+    app.add_systems(OnExit(Menu::Shop), exit_shop);
+    app.add_systems(
+        Update,
+        ((close_shop, move_cursor, buy_selected), update_shop_screen)
+            .chain()
+            .run_if(in_state(Screen::Playing).and_then(in_state(Menu::Shop)))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The entries for sale and which one is selected.
+#[derive(Resource, Debug, Default)]
+struct ShopCursor {
+    entries: Vec<ShopEntry>,
+    selected: usize,
+    /// Shown below the entries after trying to buy something.
+    message: String,
+}
+
+/// A button for the entry at this index of [`ShopCursor::entries`].
+#[derive(Component, Debug)]
+struct EntryButton(usize);
+
+/// Marker for the label showing [`ShopCursor::message`].
+#[derive(Component)]
+struct ShopMessage;
+
+fn enter_shop(
+    mut commands: Commands,
+    mut pause: ResMut<GameplayPause>,
+    open_shop: Res<OpenShop>,
+    shop_handle: Res<ShopCatalogHandle>,
+    shops: Res<Assets<ShopCatalog>>,
+    item_handle: Res<ItemCatalogHandle>,
+    items: Res<Assets<ItemCatalog>>,
+) {
+    pause.add(PauseReason::Menu);
+    let entries = open_shop
+        .0
+        .as_ref()
+        .and_then(|shop| shops.get(&shop_handle.0)?.get(shop))
+        .unwrap_or_default()
+        .to_vec();
+    let catalog = items.get(&item_handle.0);
+
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(Menu::Shop),
+            BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        ))
+        .with_children(|children| {
+            children.header("Shop");
+            for (index, entry) in entries.iter().enumerate() {
+                let name = catalog
+                    .and_then(|catalog| catalog.get(&entry.item))
+                    .map_or(entry.item.as_str(), |item| item.name.as_str());
+                let text = match entry.count {
+                    1 => name.to_string(),
+                    count => format!("{name} x{count}"),
+                };
+                children
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|children| {
+                        children.button(text).insert(EntryButton(index));
+                        children.label(format!("{} rupees", entry.price));
+                    });
+            }
+            if entries.is_empty() {
+                children.label("Nothing for sale right now.");
+            }
+            children.label("").insert(ShopMessage);
+            children.label("E: buy    Tab: leave");
+        });
+
+    commands.insert_resource(ShopCursor {
+        entries,
+        selected: 0,
+        message: String::new(),
+    });
+}
+
+fn exit_shop(
+    mut commands: Commands,
+    mut pause: ResMut<GameplayPause>,
+    mut open_shop: ResMut<OpenShop>,
+) {
+    pause.remove(PauseReason::Menu);
+    open_shop.0 = None;
+    commands.remove_resource::<ShopCursor>();
+}
+
+fn close_shop(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    let pressed = |button| gamepad_just_pressed(&gamepads, &buttons, button);
+    if kb.any_just_pressed(INVENTORY.iter().copied().chain([KeyCode::Escape]))
+        || pressed(GamepadButtonType::Start)
+        || pressed(GamepadButtonType::East)
+    {
+        next_menu.set(Menu::None);
+    }
+}
+
+fn move_cursor(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut cursor: ResMut<ShopCursor>,
+) {
+    let pressed = |keys: &[KeyCode], button| {
+        kb.any_just_pressed(keys.iter().copied())
+            || gamepad_just_pressed(&gamepads, &buttons, button)
+    };
+    if pressed(UP, GamepadButtonType::DPadUp) && cursor.selected > 0 {
+        cursor.selected -= 1;
+    } else if pressed(DOWN, GamepadButtonType::DPadDown)
+        && cursor.selected + 1 < cursor.entries.len()
+    {
+        cursor.selected += 1;
+    }
+}
+
+fn buy_selected(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut button_query: InteractionQuery<&EntryButton>,
+    mut cursor: ResMut<ShopCursor>,
+    mut save: ResMut<SaveData>,
+    item_handle: Res<ItemCatalogHandle>,
+    items: Res<Assets<ItemCatalog>>,
+    mut inventory_q: Query<&mut Inventory, With<Player>>,
+) {
+    let clicked = button_query
+        .iter_mut()
+        .find(|(interaction, _)| matches!(interaction, Interaction::Pressed))
+        .map(|(_, button)| button.0);
+    if let Some(index) = clicked {
+        cursor.selected = index;
+    }
+    let pressed = kb.any_just_pressed(BUY)
+        || gamepad_just_pressed(&gamepads, &buttons, GamepadButtonType::South);
+    if clicked.is_none() && !pressed {
+        return;
+    }
+    let (Some(entry), Some(catalog), Ok(mut inventory)) = (
+        cursor.entries.get(cursor.selected),
+        items.get(&item_handle.0),
+        inventory_q.get_single_mut(),
+    ) else {
+        return;
+    };
+    cursor.message = match buy(entry, &mut save, &mut inventory, catalog) {
+        Ok(()) => "Thank you!".to_string(),
+        Err(e) => e.to_string(),
+    };
+}
+
+fn update_shop_screen(
+    cursor: Res<ShopCursor>,
+    mut button_q: Query<(
+        &EntryButton,
+        &Interaction,
+        &mut InteractionPalette,
+        &mut BackgroundColor,
+    )>,
+    message_q: Query<&Children, With<ShopMessage>>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !cursor.is_changed() {
+        return;
+    }
+    for (button, interaction, mut palette, mut background) in &mut button_q {
+        palette.none = if button.0 == cursor.selected {
+            ui_palette::BUTTON_HOVERED_BACKGROUND
+        } else {
+            ui_palette::NODE_BACKGROUND
+        };
+        if matches!(interaction, Interaction::None) {
+            *background = palette.none.into();
+        }
+    }
+    for children in &message_q {
+        let mut iter = text_q.iter_many_mut(children.iter());
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value.clone_from(&cursor.message);
+        }
+    }
+}
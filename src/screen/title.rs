@@ -3,7 +3,10 @@
 use bevy::prelude::*;
 
 use super::Screen;
-use crate::ui::prelude::*;
+use crate::{
+    game::audio::soundtrack::{PlaySoundtrack, PlaylistKey},
+    ui::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), enter_title);
@@ -33,6 +36,8 @@ fn enter_title(mut commands: Commands) {
             #[cfg(not(target_family = "wasm"))]
             children.button("Exit").insert(TitleAction::Exit);
         });
+
+    commands.trigger(PlaySoundtrack::Playlist(PlaylistKey::Menu));
 }
 
 fn handle_title_action(
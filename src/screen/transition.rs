@@ -0,0 +1,296 @@
+//! Transitions between screens. Setting [`NextState<Screen>`] to a screen with a
+//! [`Transition`] configured in [`ScreenTransitions`] doesn't change it right away:
+//! an overlay covers the old screen first, the screen changes while it's covered,
+//! and then the overlay reveals the new one. Pairs of screens without one cut instantly.
+//! A screen change requested while a transition is running heads for the new screen
+//! instead, turning back around if the overlay was already revealing.
+
+use bevy::{prelude::*, ui::FocusPolicy, utils::HashMap};
+
+use super::Screen;
+use crate::tween::{Ease, Tween, TweenCommandsExt, TweenCompleted, TweenTarget};
+
+/// Radius of the iris overlay's hole when fully open, as a percentage of the
+/// larger window side. Big enough to uncover the corners of any window.
+const IRIS_OPEN_RADIUS: f32 = 75.0;
+/// Radius of the iris overlay, as a percentage of the larger window side.
+const IRIS_RADIUS: f32 = 150.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ScreenTransitions>();
+    app.observe(advance_transition);
+    // After anything that may request a screen change, so none gets through unnoticed.
+    app.add_systems(Last, intercept_screen_changes);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionStyle {
+    /// Fades to the color and back.
+    Fade,
+    /// The color slides in from the left and out to the right.
+    Wipe,
+    /// A circle of the color closes in on the middle of the screen and opens up again.
+    Iris,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Transition {
+    pub style: TransitionStyle,
+    pub color: Color,
+    /// How long covering the old screen takes, in seconds.
+    pub cover_secs: f32,
+    /// How long revealing the new screen takes, in seconds.
+    pub reveal_secs: f32,
+    pub ease: Ease,
+}
+
+/// The transition used to go from one screen to another, keyed by both.
+#[derive(Resource, Deref, DerefMut)]
+pub struct ScreenTransitions(HashMap<(Screen, Screen), Transition>);
+
+impl Default for ScreenTransitions {
+    fn default() -> Self {
+        let fade = Transition {
+            style: TransitionStyle::Fade,
+            color: Color::BLACK,
+            cover_secs: 0.4,
+            reveal_secs: 0.4,
+            ease: Ease::QuadInOut,
+        };
+        let wipe = Transition {
+            style: TransitionStyle::Wipe,
+            cover_secs: 0.3,
+            reveal_secs: 0.3,
+            ease: Ease::CubicInOut,
+            ..fade
+        };
+        let iris = Transition {
+            style: TransitionStyle::Iris,
+            cover_secs: 0.6,
+            reveal_secs: 0.4,
+            ease: Ease::QuadIn,
+            ..fade
+        };
+        Self(
+            [
+                ((Screen::Loading, Screen::Title), fade),
+                ((Screen::Title, Screen::Playing), fade),
+                ((Screen::Playing, Screen::Title), iris),
+                ((Screen::Title, Screen::Credits), wipe),
+                ((Screen::Credits, Screen::Title), wipe),
+            ]
+            .into(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Covering,
+    /// Covered, waiting for the screen to change.
+    Switching,
+    Revealing,
+}
+
+/// The transition that's running, if any.
+#[derive(Resource, Debug)]
+struct ActiveTransition {
+    to: Screen,
+    transition: Transition,
+    phase: Phase,
+    /// Real time at which the current phase's tween started, in seconds.
+    started: f32,
+    overlay: Entity,
+}
+
+impl Transition {
+    /// A tween of the overlay from covering `from` of the screen to covering `to` of it,
+    /// both from 0 to 1. The part of a wipe that isn't covering the screen is off to
+    /// the right of it with `right` set, where wipes leave to, and to the left otherwise.
+    fn tween(&self, secs: f32, from: f32, to: f32, right: bool) -> Tween {
+        let target = match self.style {
+            TransitionStyle::Fade => {
+                let alpha = self.color.alpha();
+                TweenTarget::BackgroundColor {
+                    from: self.color.with_alpha(alpha * from),
+                    to: self.color.with_alpha(alpha * to),
+                }
+            }
+            TransitionStyle::Wipe => {
+                let side = if right { 100.0 } else { -100.0 };
+                TweenTarget::UiLeft {
+                    from: Val::Percent(side * (1.0 - from)),
+                    to: Val::Percent(side * (1.0 - to)),
+                }
+            }
+            TransitionStyle::Iris => {
+                let open = IRIS_RADIUS - IRIS_OPEN_RADIUS;
+                TweenTarget::UiBorder {
+                    from: Val::VMax(open + IRIS_OPEN_RADIUS * from),
+                    to: Val::VMax(open + IRIS_OPEN_RADIUS * to),
+                }
+            }
+        };
+        Tween::new(secs, self.ease, target).in_real_time()
+    }
+}
+
+fn intercept_screen_changes(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    screen: Res<State<Screen>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    transitions: Res<ScreenTransitions>,
+    active: Option<ResMut<ActiveTransition>>,
+) {
+    let NextState::Pending(to) = next_screen.as_ref() else {
+        return;
+    };
+    let to = to.clone();
+    let now = time.elapsed_seconds();
+    if let Some(mut active) = active {
+        let transition = active.transition;
+        match active.phase {
+            // The transition's own screen change, or one requested while the screen
+            // is covered anyway. Either way, the new screen can be revealed.
+            Phase::Switching => {
+                active.phase = Phase::Revealing;
+                active.started = now;
+                commands.entity(active.overlay).tween(transition.tween(
+                    transition.reveal_secs,
+                    1.0,
+                    0.0,
+                    true,
+                ));
+            }
+            Phase::Covering => next_screen.reset(),
+            // Cover the screen up again from as far as it's uncovered, taking as long
+            // as it took to get there. The overlay goes back the way it left.
+            Phase::Revealing => {
+                next_screen.reset();
+                let progress = if transition.reveal_secs > 0.0 {
+                    ((now - active.started) / transition.reveal_secs).min(1.0)
+                } else {
+                    1.0
+                };
+                let coverage = 1.0 - transition.ease.apply(progress);
+                active.phase = Phase::Covering;
+                active.started = now;
+                commands.entity(active.overlay).tween(transition.tween(
+                    transition.cover_secs * progress,
+                    coverage,
+                    1.0,
+                    true,
+                ));
+            }
+        }
+        active.to = to;
+        return;
+    }
+
+    let Some(transition) = transitions
+        .get(&(screen.get().clone(), to.clone()))
+        .copied()
+    else {
+        return;
+    };
+    next_screen.reset();
+    let overlay = spawn_overlay(&mut commands, &transition);
+    commands
+        .entity(overlay)
+        .tween(transition.tween(transition.cover_secs, 0.0, 1.0, false));
+    commands.insert_resource(ActiveTransition {
+        to,
+        transition,
+        phase: Phase::Covering,
+        started: now,
+        overlay,
+    });
+}
+
+fn spawn_overlay(commands: &mut Commands, transition: &Transition) -> Entity {
+    let (style, background, border, border_radius) = match transition.style {
+        TransitionStyle::Fade => (
+            Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            Color::NONE,
+            Color::NONE,
+            BorderRadius::ZERO,
+        ),
+        TransitionStyle::Wipe => (
+            Style {
+                left: Val::Percent(-100.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            transition.color,
+            Color::NONE,
+            BorderRadius::ZERO,
+        ),
+        // A circle with a round hole in the middle, which closes as the border grows.
+        TransitionStyle::Iris => (
+            Style {
+                left: Val::Percent(50.0),
+                top: Val::Percent(50.0),
+                width: Val::VMax(2.0 * IRIS_RADIUS),
+                height: Val::VMax(2.0 * IRIS_RADIUS),
+                margin: UiRect {
+                    left: Val::VMax(-IRIS_RADIUS),
+                    top: Val::VMax(-IRIS_RADIUS),
+                    ..default()
+                },
+                border: UiRect::all(Val::VMax(IRIS_RADIUS - IRIS_OPEN_RADIUS)),
+                ..default()
+            },
+            Color::NONE,
+            transition.color,
+            BorderRadius::MAX,
+        ),
+    };
+    commands
+        .spawn((
+            Name::new("Screen Transition"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..style
+                },
+                background_color: BackgroundColor(background),
+                border_color: BorderColor(border),
+                border_radius,
+                // Keep buttons on the screens below from being clicked.
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+/// Changes the screen once the overlay covers it, and ends the transition
+/// once the overlay is out of the way again.
+fn advance_transition(
+    trigger: Trigger<TweenCompleted>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    active: Option<ResMut<ActiveTransition>>,
+) {
+    let Some(mut active) = active.filter(|active| active.overlay == trigger.entity()) else {
+        return;
+    };
+    match active.phase {
+        Phase::Covering => {
+            next_screen.set(active.to.clone());
+            active.phase = Phase::Switching;
+        }
+        Phase::Switching => {}
+        Phase::Revealing => {
+            commands.entity(active.overlay).despawn_recursive();
+            commands.remove_resource::<ActiveTransition>();
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! Tweens: animating a property from one value to another over a set time, with easing.
+//! A [`Tween`] is a sequence of steps, each animating one [`TweenTarget`] or waiting.
+//! It can repeat, and play every other repetition backwards when it's a yoyo.
+//! Add one with [`TweenCommandsExt::tween`]. An entity can run several tweens at once,
+//! as long as they animate different properties. A new tween replaces any running one
+//! that animates the same property, without completing it.
+//! [`TweenCompleted`] is triggered on the entity when a tween is done.
+//!
+//! Tweens are for motion with a known end, like fades. Following something that keeps
+//! moving, like the camera following the player, is better done with [`SmoothNudge`].
+//!
+//! [`SmoothNudge`]: crate::utils::SmoothNudge
+
+use std::{f32::consts::PI, mem};
+
+use bevy::{color::Mix, ecs::system::EntityCommands, prelude::*};
+use serde::Deserialize;
+
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Ease>();
+    app.add_systems(Update, update_tweens.in_set(AppSet::Update));
+}
+
+/// How a tween speeds up and slows down.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    /// Pulls back a little before starting.
+    BackIn,
+    /// Overshoots a little before settling.
+    BackOut,
+    /// Bounces to a stop, like a dropped ball.
+    BounceOut,
+}
+
+impl Ease {
+    /// Maps linear progress `t`, from 0 to 1, to eased progress.
+    pub fn apply(self, t: f32) -> f32 {
+        const BACK: f32 = 1.70158;
+        match self {
+            Self::Linear => t,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Self::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Self::QuadInOut => 1.0 - (2.0 - 2.0 * t).powi(2) / 2.0,
+            Self::CubicIn => t.powi(3),
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut if t < 0.5 => 4.0 * t.powi(3),
+            Self::CubicInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+            Self::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Self::SineOut => (t * PI / 2.0).sin(),
+            Self::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+            Self::BackIn => (BACK + 1.0) * t.powi(3) - BACK * t * t,
+            Self::BackOut => 1.0 + (BACK + 1.0) * (t - 1.0).powi(3) + BACK * (t - 1.0).powi(2),
+            Self::BounceOut => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+/// A property a tween animates, and the values it goes between.
+#[derive(Clone, Copy, Debug)]
+pub enum TweenTarget {
+    Translation {
+        from: Vec3,
+        to: Vec3,
+    },
+    Scale {
+        from: Vec3,
+        to: Vec3,
+    },
+    /// The color of a [`Sprite`].
+    SpriteColor {
+        from: Color,
+        to: Color,
+    },
+    /// The color of a [`UiImage`].
+    UiImageColor {
+        from: Color,
+        to: Color,
+    },
+    BackgroundColor {
+        from: Color,
+        to: Color,
+    },
+    /// The `left` of a UI node's [`Style`].
+    UiLeft {
+        from: Val,
+        to: Val,
+    },
+    /// The width of all four sides of a UI node's border.
+    UiBorder {
+        from: Val,
+        to: Val,
+    },
+    /// The scale of an [`OrthographicProjection`], like the camera's zoom.
+    CameraScale {
+        from: f32,
+        to: f32,
+    },
+}
+
+impl TweenTarget {
+    fn apply(&self, t: f32, entity: &mut Tweened) {
+        let mix = |from: &Color, to: &Color| {
+            Color::from(LinearRgba::from(*from).mix(&LinearRgba::from(*to), t))
+        };
+        match self {
+            Self::Translation { from, to } => {
+                if let Some(transform) = &mut entity.transform {
+                    transform.translation = from.lerp(*to, t);
+                }
+            }
+            Self::Scale { from, to } => {
+                if let Some(transform) = &mut entity.transform {
+                    transform.scale = from.lerp(*to, t);
+                }
+            }
+            Self::SpriteColor { from, to } => {
+                if let Some(sprite) = &mut entity.sprite {
+                    sprite.color = mix(from, to);
+                }
+            }
+            Self::UiImageColor { from, to } => {
+                if let Some(image) = &mut entity.image {
+                    image.color = mix(from, to);
+                }
+            }
+            Self::BackgroundColor { from, to } => {
+                if let Some(background) = &mut entity.background {
+                    background.0 = mix(from, to);
+                }
+            }
+            Self::UiLeft { from, to } => {
+                if let Some(style) = &mut entity.style {
+                    style.left = lerp_val(*from, *to, t);
+                }
+            }
+            Self::UiBorder { from, to } => {
+                if let Some(style) = &mut entity.style {
+                    style.border = UiRect::all(lerp_val(*from, *to, t));
+                }
+            }
+            Self::CameraScale { from, to } => {
+                if let Some(projection) = &mut entity.projection {
+                    projection.scale = from.lerp(*to, t);
+                }
+            }
+        }
+    }
+
+    /// Whether both targets animate the same property.
+    fn overlaps(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+/// Goes from one [`Val`] to another of the same unit. Values of different units
+/// can't be mixed, so they switch over at the end.
+fn lerp_val(from: Val, to: Val, t: f32) -> Val {
+    match (from, to) {
+        (Val::Px(a), Val::Px(b)) => Val::Px(a.lerp(b, t)),
+        (Val::Percent(a), Val::Percent(b)) => Val::Percent(a.lerp(b, t)),
+        (Val::Vw(a), Val::Vw(b)) => Val::Vw(a.lerp(b, t)),
+        (Val::Vh(a), Val::Vh(b)) => Val::Vh(a.lerp(b, t)),
+        (Val::VMin(a), Val::VMin(b)) => Val::VMin(a.lerp(b, t)),
+        (Val::VMax(a), Val::VMax(b)) => Val::VMax(a.lerp(b, t)),
+        _ if t < 1.0 => from,
+        _ => to,
+    }
+}
+
+/// How many times a tween plays.
+#[derive(Clone, Copy, Debug)]
+pub enum Repeat {
+    /// Plays this many times in all. Tweens play once by default.
+    Times(u32),
+    Forever,
+}
+
+#[derive(Clone, Debug)]
+struct TweenStep {
+    secs: f32,
+    ease: Ease,
+    /// Nothing is animated by a step that just waits.
+    target: Option<TweenTarget>,
+}
+
+/// A sequence of steps animating properties of an entity.
+#[derive(Clone, Debug)]
+pub struct Tween {
+    steps: Vec<TweenStep>,
+    repeat: Repeat,
+    yoyo: bool,
+    real_time: bool,
+    /// Seconds into the current play.
+    elapsed: f32,
+    /// How many plays are done.
+    played: u32,
+}
+
+impl Tween {
+    pub fn new(secs: f32, ease: Ease, target: TweenTarget) -> Self {
+        Self {
+            steps: vec![],
+            repeat: Repeat::Times(1),
+            yoyo: false,
+            real_time: false,
+            elapsed: 0.0,
+            played: 0,
+        }
+        .then(secs, ease, target)
+    }
+
+    /// Adds a step after the others.
+    pub fn then(mut self, secs: f32, ease: Ease, target: TweenTarget) -> Self {
+        self.steps.push(TweenStep {
+            secs: secs.max(0.0),
+            ease,
+            target: Some(target),
+        });
+        self
+    }
+
+    /// Adds a step that waits, after the others.
+    pub fn wait(mut self, secs: f32) -> Self {
+        self.steps.push(TweenStep {
+            secs: secs.max(0.0),
+            ease: Ease::Linear,
+            target: None,
+        });
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays every other repetition backwards.
+    pub fn yoyo(mut self) -> Self {
+        self.yoyo = true;
+        self
+    }
+
+    /// Keeps playing while virtual time is paused, like when a menu is open.
+    pub fn in_real_time(mut self) -> Self {
+        self.real_time = true;
+        self
+    }
+
+    fn duration(&self) -> f32 {
+        self.steps.iter().map(|step| step.secs).sum()
+    }
+
+    fn targets(&self) -> impl Iterator<Item = &TweenTarget> {
+        self.steps.iter().filter_map(|step| step.target.as_ref())
+    }
+
+    /// Moves the tween `dt` seconds on, applying every step it passes through, in order.
+    /// Returns whether it's done.
+    fn advance(&mut self, dt: f32, entity: &mut Tweened) -> bool {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            self.apply_between(0.0, 0.0, entity);
+            return true;
+        }
+        let mut from = self.elapsed;
+        self.elapsed += dt;
+        loop {
+            self.apply_between(from, self.elapsed.min(duration), entity);
+            if self.elapsed < duration {
+                return false;
+            }
+            self.played += 1;
+            if let Repeat::Times(times) = self.repeat {
+                if self.played >= times {
+                    return true;
+                }
+            }
+            self.elapsed -= duration;
+            from = 0.0;
+        }
+    }
+
+    /// Applies the steps between two points of the current play, in the order they're played.
+    /// Steps that were passed entirely are left at their end.
+    fn apply_between(&self, from: f32, to: f32, entity: &mut Tweened) {
+        let duration = self.duration();
+        let backwards = self.yoyo && self.played % 2 == 1;
+        let (from, to) = if backwards {
+            (duration - from, duration - to)
+        } else {
+            (from, to)
+        };
+        let (low, high) = (from.min(to), from.max(to));
+
+        let mut start = 0.0;
+        let mut passed = vec![];
+        for step in &self.steps {
+            let end = start + step.secs;
+            if let (Some(target), true) = (&step.target, end >= low && start <= high) {
+                let t = if step.secs > 0.0 {
+                    ((to - start) / step.secs).clamp(0.0, 1.0)
+                } else if to >= start {
+                    1.0
+                } else {
+                    0.0
+                };
+                passed.push((target, step.ease.apply(t)));
+            }
+            start = end;
+        }
+        if backwards {
+            passed.reverse();
+        }
+        for (target, t) in passed {
+            target.apply(t, entity);
+        }
+    }
+}
+
+/// The tweens an entity is running. Add them with [`TweenCommandsExt::tween`].
+#[derive(Component, Debug)]
+pub struct Tweens(Vec<Tween>);
+
+impl Tweens {
+    fn add(&mut self, tween: Tween) {
+        self.0.retain(|running| {
+            !running
+                .targets()
+                .any(|a| tween.targets().any(|b| a.overlaps(b)))
+        });
+        self.0.push(tween);
+    }
+}
+
+/// Triggered on an entity when one of its tweens is done.
+/// Tweens that repeat forever or are replaced are never done.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TweenCompleted;
+
+pub trait TweenCommandsExt {
+    /// Starts a tween on the entity, replacing any that animates the same property.
+    fn tween(&mut self, tween: Tween) -> &mut Self;
+}
+
+impl TweenCommandsExt for EntityCommands<'_> {
+    fn tween(&mut self, tween: Tween) -> &mut Self {
+        self.add(
+            move |mut entity: EntityWorldMut| match entity.get_mut::<Tweens>() {
+                Some(mut tweens) => tweens.add(tween),
+                None => {
+                    entity.insert(Tweens(vec![tween]));
+                }
+            },
+        )
+    }
+}
+
+/// The properties of an entity that tweens can animate.
+struct Tweened<'a> {
+    transform: Option<Mut<'a, Transform>>,
+    sprite: Option<Mut<'a, Sprite>>,
+    image: Option<Mut<'a, UiImage>>,
+    background: Option<Mut<'a, BackgroundColor>>,
+    style: Option<Mut<'a, Style>>,
+    projection: Option<Mut<'a, OrthographicProjection>>,
+}
+
+fn update_tweens(
+    mut commands: Commands,
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+    mut tween_q: Query<(
+        Entity,
+        &mut Tweens,
+        Option<&mut Transform>,
+        Option<&mut Sprite>,
+        Option<&mut UiImage>,
+        Option<&mut BackgroundColor>,
+        Option<&mut Style>,
+        Option<&mut OrthographicProjection>,
+    )>,
+) {
+    for (entity, mut tweens, transform, sprite, image, background, style, projection) in
+        &mut tween_q
+    {
+        let mut tweened = Tweened {
+            transform,
+            sprite,
+            image,
+            background,
+            style,
+            projection,
+        };
+        let mut completed = 0;
+        tweens.0.retain_mut(|tween| {
+            let dt = if tween.real_time {
+                real_time.delta_seconds()
+            } else {
+                time.delta_seconds()
+            };
+            let done = tween.advance(dt, &mut tweened);
+            completed += usize::from(done);
+            !done
+        });
+        for _ in 0..completed {
+            commands.trigger_targets(TweenCompleted, entity);
+        }
+    }
+}
@@ -8,3 +8,6 @@ pub const LABEL_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 pub const HEADER_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 
 pub const NODE_BACKGROUND: Color = Color::srgb(0.286, 0.478, 0.773);
+
+pub const PROGRESS_BAR_BACKGROUND: Color = Color::srgb(0.157, 0.157, 0.157);
+pub const PROGRESS_BAR_FILL: Color = Color::srgb(0.867, 0.827, 0.412);
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ProgressBar>();
+    app.add_systems(Update, apply_progress_bar);
+}
+
+/// Fraction between `0.0` and `1.0` shown by a progress bar widget.
+/// The bar's first child is stretched to match it.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct ProgressBar(pub f32);
+
+fn apply_progress_bar(
+    bar_query: Query<(&ProgressBar, &Children), Changed<ProgressBar>>,
+    mut fill_query: Query<&mut Style>,
+) {
+    for (ProgressBar(fraction), children) in &bar_query {
+        let Some(mut style) = children
+            .first()
+            .and_then(|&fill| fill_query.get_mut(fill).ok())
+        else {
+            continue;
+        };
+        style.width = Val::Percent(fraction.clamp(0.0, 1.0) * 100.0);
+    }
+}
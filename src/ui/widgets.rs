@@ -2,7 +2,7 @@
 
 use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
 
-use super::{interaction::InteractionPalette, palette::*};
+use super::{interaction::InteractionPalette, palette::*, progress::ProgressBar};
 
 /// An extension trait for spawning UI widgets.
 pub trait Widgets {
@@ -14,6 +14,9 @@ pub trait Widgets {
 
     /// Spawn a simple text label.
     fn label(&mut self, text: impl Into<String>) -> EntityCommands;
+
+    /// Spawn an empty progress bar. Fill it through its [`ProgressBar`].
+    fn progress_bar(&mut self) -> EntityCommands;
 }
 
 impl<T: Spawn> Widgets for T {
@@ -112,6 +115,37 @@ impl<T: Spawn> Widgets for T {
         });
         entity
     }
+
+    fn progress_bar(&mut self) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Progress Bar"),
+            NodeBundle {
+                style: Style {
+                    width: Px(500.0),
+                    height: Px(24.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(PROGRESS_BAR_BACKGROUND),
+                ..default()
+            },
+            ProgressBar::default(),
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Progress Bar Fill"),
+                NodeBundle {
+                    style: Style {
+                        width: Percent(0.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(PROGRESS_BAR_FILL),
+                    ..default()
+                },
+            ));
+        });
+        entity
+    }
 }
 
 /// An extension trait for spawning UI containers.
@@ -29,6 +29,17 @@ impl SmoothNudge for f32 {
     }
 }
 
+/// Whether `button` was just pressed on any connected gamepad.
+pub fn gamepad_just_pressed(
+    gamepads: &Gamepads,
+    buttons: &ButtonInput<GamepadButton>,
+    button: GamepadButtonType,
+) -> bool {
+    gamepads
+        .iter()
+        .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button)))
+}
+
 pub fn get_vec(
     kb: &Res<ButtonInput<KeyCode>>,
     left: &[KeyCode],